@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use crate::digest::Digest;
+use crate::github;
+use crate::models::configuration::{File, Source};
+use crate::models::resolved_artifact::ResolvedArtifact;
+use crate::providers::downloader::Downloader;
+use crate::template;
+
+/// Everything the URL and digest of an artifact depend on, worked out
+/// without any request.
+pub fn cache_key(configuration: &File, os: &str, arch: &str) -> String {
+    let location = match configuration.source {
+        Source::Url => {
+            template::render(&configuration.url_pattern, &configuration.version, os, arch)
+        }
+        Source::Github => github::asset_key(configuration, os, arch),
+    };
+    let checksums_url = configuration
+        .checksums_url
+        .as_ref()
+        .map(|url| template::render(url, &configuration.version, os, arch))
+        .unwrap_or_default();
+    let checksums_algorithm = configuration
+        .checksums_algorithm
+        .map(|algorithm| algorithm.name())
+        .unwrap_or_default();
+    let digests = configuration
+        .platforms
+        .get(os)
+        .and_then(|platform| platform.get(arch))
+        .map(|architecture| {
+            [
+                architecture.digest.as_ref().map(Digest::to_string),
+                architecture.sha512.clone(),
+                architecture.blake3.clone(),
+                architecture.sha256.clone(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+        })
+        .unwrap_or_default();
+    // Verified before the digest is cached, so trusting another key must
+    // resolve again.
+    let checksums_signature = configuration
+        .checksums_signature
+        .as_ref()
+        .and_then(|signature| serde_json::to_string(signature).ok())
+        .unwrap_or_default();
+    format!(
+        "{} {} {} {} {}",
+        location, checksums_url, checksums_algorithm, checksums_signature, digests
+    )
+}
+
+/// Resolves the URL and digest of the artifact with `downloader`, reusing
+/// the resolution cached in `cache_path` while the configuration is the same.
+pub async fn resolve<D: Downloader>(
+    downloader: &D,
+    configuration: &File,
+    os: &str,
+    arch: &str,
+    cache_path: &Path,
+) -> Result<(String, Digest), Box<dyn std::error::Error>> {
+    let key = cache_key(configuration, os, arch);
+    if let Ok(content) = tokio::fs::read_to_string(cache_path).await
+        && let Ok(cached) = serde_yaml::from_str::<ResolvedArtifact>(&content)
+        && cached.key == key
+    {
+        return Ok((cached.url, cached.digest));
+    }
+
+    let (url, digest) = downloader
+        .build_url_and_digest(configuration, os, arch)
+        .await?;
+    let resolved = ResolvedArtifact {
+        key,
+        url: url.clone(),
+        digest: digest.clone(),
+    };
+    tokio::fs::write(cache_path, serde_yaml::to_string(&resolved)?)
+        .await
+        .map_err(|e| format!("Failed to write file {}, {}", cache_path.display(), e))?;
+    Ok((url, digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configuration(sha256: &str) -> File {
+        serde_yaml::from_str(&format!(
+            r#"
+name: tool
+version: 1.0.0
+urlPattern: https://example.com/tool-{{{{version}}}}-{{{{os}}}}-{{{{arch}}}}
+platforms:
+    linux:
+        amd64:
+            sha256: {}
+"#,
+            sha256
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_version_and_hash() {
+        // Arrange
+        let original = configuration("abc");
+        let mut bumped = configuration("abc");
+        bumped.version = "1.1.0".to_string();
+
+        // Act
+        let key = cache_key(&original, "linux", "amd64");
+
+        // Assert
+        assert!(key.contains("https://example.com/tool-1.0.0-linux-amd64"));
+        assert_ne!(key, cache_key(&bumped, "linux", "amd64"));
+        assert_ne!(key, cache_key(&configuration("def"), "linux", "amd64"));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::digest::{Algorithm, Digest};
+
+/// Finds the digest of `file_name` in a checksums file.
+///
+/// Both the GNU format `sha256sum` writes (`<hash>  <name>`, with `*` before
+/// the name in binary mode) and the BSD format (`SHA256 (<name>) = <hash>`)
+/// are understood. Names are compared without their directory, since such
+/// files often list paths like `./dist/tool.tar.gz`; an exact match wins over
+/// a basename one.
+///
+/// The algorithm is the BSD tag, or else `algorithm`, or else told apart by
+/// the length of the hash: SHA256 or SHA512.
+pub fn find(content: &str, file_name: &str, algorithm: Option<Algorithm>) -> Option<Digest> {
+    let entries: Vec<(&str, Digest)> = content
+        .lines()
+        .filter_map(|line| parse_line(line, algorithm))
+        .collect();
+    entries
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|(name, _)| basename(name) == basename(file_name))
+        })
+        .map(|(_, hash)| hash.clone())
+}
+
+/// The name and digest on one line, if it lists one of `algorithm` when
+/// that is given.
+fn parse_line(line: &str, algorithm: Option<Algorithm>) -> Option<(&str, Digest)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (name, hash, tagged) = match parse_bsd_line(line) {
+        Some((tag, name, hash)) => {
+            if algorithm.is_some_and(|algorithm| algorithm != tag) {
+                return None;
+            }
+            (name, hash, Some(tag))
+        }
+        None => {
+            let (hash, name) = line.split_once(char::is_whitespace)?;
+            let name = name.trim_start();
+            (name.strip_prefix('*').unwrap_or(name), hash, None)
+        }
+    };
+    let algorithm = tagged.or(algorithm).or(match hash.len() {
+        64 => Some(Algorithm::Sha256),
+        128 => Some(Algorithm::Sha512),
+        _ => None,
+    })?;
+    Some((name, Digest::new(algorithm, hash).ok()?))
+}
+
+/// The algorithm, name and hash of a `<TAG> (<name>) = <hash>` line.
+fn parse_bsd_line(line: &str) -> Option<(Algorithm, &str, &str)> {
+    let (tag, rest) = line.split_once(" (")?;
+    let algorithm = Algorithm::from_name(tag)?;
+    let (name, hash) = rest.rsplit_once(") = ")?;
+    Some((algorithm, name, hash.trim()))
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static LINUX: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    static DARWIN: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+    #[test]
+    fn test_find_in_gnu_format() {
+        // Arrange
+        let content = format!(
+            "{}  tool_1.0.0_darwin_arm64.zip\n{}  tool_1.0.0_linux_amd64.zip\n",
+            DARWIN,
+            LINUX.to_uppercase()
+        );
+
+        // Act
+        let hash = find(&content, "tool_1.0.0_linux_amd64.zip", None);
+
+        // Assert
+        assert_eq!(hash.map(|digest| digest.hex).as_deref(), Some(LINUX));
+        assert_eq!(find(&content, "tool_1.0.0_linux_arm64.zip", None), None);
+    }
+
+    #[test]
+    fn test_find_with_binary_marker_and_crlf() {
+        // Arrange
+        let content = format!("{} *tool.tar.gz\r\n", LINUX);
+
+        // Act
+        let hash = find(&content, "tool.tar.gz", None);
+
+        // Assert
+        assert_eq!(hash.map(|digest| digest.hex).as_deref(), Some(LINUX));
+    }
+
+    #[test]
+    fn test_find_in_bsd_format() {
+        // Arrange
+        let content = format!(
+            "SHA256 (tool (darwin).zip) = {}\nSHA256 (tool.zip) = {}\n",
+            DARWIN, LINUX
+        );
+
+        // Act
+        let hash = find(&content, "tool.zip", None);
+
+        // Assert
+        assert_eq!(hash.map(|digest| digest.hex).as_deref(), Some(LINUX));
+        assert_eq!(
+            find(&content, "tool (darwin).zip", None)
+                .map(|digest| digest.hex)
+                .as_deref(),
+            Some(DARWIN)
+        );
+    }
+
+    #[test]
+    fn test_find_matches_basename_and_prefers_exact_name() {
+        // Arrange
+        let content = format!(
+            "# release checksums\n{}  ./dist/tool.zip\n{}  tool.zip.sig\n",
+            LINUX, DARWIN
+        );
+
+        // Act
+        let hash = find(&content, "tool.zip", None);
+
+        // Assert
+        assert_eq!(hash.map(|digest| digest.hex).as_deref(), Some(LINUX));
+        assert_eq!(
+            find(
+                &format!("{}  dist/tool.zip\n{}  tool.zip\n", DARWIN, LINUX),
+                "tool.zip",
+                None
+            )
+            .map(|digest| digest.hex)
+            .as_deref(),
+            Some(LINUX)
+        );
+    }
+
+    #[test]
+    fn test_find_ignores_other_digests() {
+        // Arrange
+        let content = "d41d8cd98f00b204e9800998ecf8427e  tool.zip\nSHA512 (tool.zip) = abc\n";
+
+        // Act
+        let hash = find(content, "tool.zip", None);
+
+        // Assert
+        assert_eq!(hash, None);
+    }
+
+    #[test]
+    fn test_find_tells_algorithms_apart() {
+        // Arrange
+        let sha512 = "c".repeat(128);
+        let content = format!(
+            "{}  tool.zip
+BLAKE3 (tool.tar.gz) = {}
+{}  tool.tgz
+",
+            sha512, LINUX, DARWIN
+        );
+
+        // Act
+        let zip = find(&content, "tool.zip", None).unwrap();
+        let tarball = find(&content, "tool.tar.gz", None).unwrap();
+        let blake3 = find(&content, "tool.tgz", Some(Algorithm::Blake3)).unwrap();
+
+        // Assert
+        assert_eq!(zip, Digest::new(Algorithm::Sha512, &sha512).unwrap());
+        assert_eq!(tarball, Digest::new(Algorithm::Blake3, LINUX).unwrap());
+        assert_eq!(blake3, Digest::new(Algorithm::Blake3, DARWIN).unwrap());
+        assert_eq!(find(&content, "tool.tar.gz", Some(Algorithm::Sha256)), None);
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::commands::{Arguments, shim};
+use crate::credentials::CredentialSources;
+use crate::digest::{Algorithm, Digest};
+use crate::github;
+use crate::models::configuration::{File, PlatformOSArchitecture, Source};
+use crate::providers::authenticated_downloader::AuthenticatedDownloader;
+use crate::providers::downloader::Downloader;
+use crate::providers::github_downloader::GithubDownloader;
+use crate::providers::http_download;
+use crate::providers::unauthenticated_downloader::UnauthenticatedDownloader;
+
+static USAGE: &str = "Usage: launcher bump [--dry-run] [--bin-dir <dir>] <config> <version>";
+
+/// The new digests of each `(os, arch)`, one per algorithm it lists.
+type Digests = BTreeMap<(String, String), Vec<Digest>>;
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let arguments = Arguments::parse(args, &["dry-run"])?;
+    let [config_path, version] = arguments.positional.as_slice() else {
+        return Err(USAGE.into());
+    };
+    let path = Path::new(config_path);
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read file {}, {}", path.display(), e))?;
+    let mut configuration: File = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Failed to parse configuration {}, {}", path.display(), e))?;
+
+    configuration.version = version.clone();
+    let digests = rehash(&configuration).await?;
+    let bumped = rewrite(&content, version, &digests)?;
+
+    if arguments.is_set("dry-run") {
+        print!("{}", diff(&path.display().to_string(), &content, &bumped));
+        return Ok(());
+    }
+    tokio::fs::write(path, bumped)
+        .await
+        .map_err(|e| format!("Failed to write file {}, {}", path.display(), e))?;
+    eprintln!("Bumped {} to {}", configuration.name, version);
+
+    // Shebang shims are copies of the configuration, so they are bumped too.
+    let bin_location = arguments
+        .value("bin-dir")
+        .unwrap_or(shim::DEFAULT_BIN_LOCATION);
+    let bin_dir = PathBuf::from(shellexpand::tilde(bin_location).as_ref());
+    let launcher = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the launcher executable, {}", e))?;
+    for shim_path in shim::refresh(path, &bin_dir, &launcher).await? {
+        eprintln!("Updated {}", shim_path.display());
+    }
+    Ok(())
+}
+
+/// Downloads the artifact of every platform of `configuration` to hash it
+/// with each algorithm it has a digest for. Platforms without one, whose
+/// digest comes from `checksumsUrl`, are left alone.
+async fn rehash(configuration: &File) -> Result<Digests, Box<dyn std::error::Error>> {
+    let github_downloader = match configuration.source {
+        Source::Github => Some(GithubDownloader::with_credential(
+            github::credential(configuration).await?,
+        )),
+        Source::Url => None,
+    };
+    let credentials = CredentialSources::from_environment();
+
+    let mut digests = Digests::new();
+    for (os, platform) in &configuration.platforms {
+        for (arch, architecture) in platform {
+            let algorithms = algorithms(architecture);
+            if algorithms.is_empty() {
+                continue;
+            }
+            eprintln!("Hashing {}/{}...", os, arch);
+            let platform_digests = match &github_downloader {
+                Some(downloader) => hash(downloader, configuration, os, arch, &algorithms).await?,
+                None => {
+                    let url = http_download::build_url(configuration, os, arch)?;
+                    match credentials
+                        .resolve(configuration.credential.as_deref(), &url)
+                        .await?
+                    {
+                        Some(credential) => {
+                            let downloader = AuthenticatedDownloader::with_credential(credential);
+                            hash(&downloader, configuration, os, arch, &algorithms).await?
+                        }
+                        None => {
+                            let downloader = UnauthenticatedDownloader::new();
+                            hash(&downloader, configuration, os, arch, &algorithms).await?
+                        }
+                    }
+                }
+            };
+            digests.insert((os.clone(), arch.clone()), platform_digests);
+        }
+    }
+    Ok(digests)
+}
+
+/// The algorithms of the digests `architecture` lists.
+fn algorithms(architecture: &PlatformOSArchitecture) -> Vec<Algorithm> {
+    let mut algorithms = Vec::new();
+    for (algorithm, listed) in [
+        (Algorithm::Sha256, architecture.sha256.is_some()),
+        (Algorithm::Sha512, architecture.sha512.is_some()),
+        (Algorithm::Blake3, architecture.blake3.is_some()),
+    ] {
+        if listed {
+            algorithms.push(algorithm);
+        }
+    }
+    if let Some(digest) = &architecture.digest
+        && !algorithms.contains(&digest.algorithm)
+    {
+        algorithms.push(digest.algorithm);
+    }
+    algorithms
+}
+
+async fn hash<D: Downloader>(
+    downloader: &D,
+    configuration: &File,
+    os: &str,
+    arch: &str,
+    algorithms: &[Algorithm],
+) -> Result<Vec<Digest>, Box<dyn std::error::Error>> {
+    let (url, _) = downloader
+        .build_url_and_digest(configuration, os, arch)
+        .await?;
+    downloader
+        .fetch_digests(configuration, &url, algorithms)
+        .await
+}
+
+/// Rewrites the `version` and the digests of `platforms` in `content`, line
+/// by line, so that comments, the `#!` line and the layout are kept.
+fn rewrite(content: &str, version: &str, digests: &Digests) -> Result<String, String> {
+    let mut rewritten = String::with_capacity(content.len());
+    // The keys of the mappings the current line is nested in, with their
+    // indentation.
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    let mut version_found = false;
+    let mut platforms_found = BTreeSet::new();
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let Some((key, value)) = key_value(trimmed) else {
+            rewritten.push_str(line);
+            continue;
+        };
+        let indent = line.len() - trimmed.len();
+        while parents.last().is_some_and(|(parent, _)| *parent >= indent) {
+            parents.pop();
+        }
+        let path: Vec<&str> = parents
+            .iter()
+            .map(|(_, key)| key.as_str())
+            .chain([key.as_str()])
+            .collect();
+
+        let replacement = match path.as_slice() {
+            ["version"] => {
+                version_found = true;
+                Some(version.to_string())
+            }
+            ["platforms", os, arch, field] => {
+                match digests.get(&(os.to_string(), arch.to_string())) {
+                    Some(platform_digests) => {
+                        let digest = new_digest(field, scalar(value), platform_digests);
+                        if digest.is_some() {
+                            platforms_found.insert((os.to_string(), arch.to_string()));
+                        }
+                        digest
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        match replacement {
+            Some(replacement) => rewritten.push_str(&replace_value(line, &replacement)),
+            None => rewritten.push_str(line),
+        }
+        if scalar(value).is_empty() {
+            parents.push((indent, key));
+        }
+    }
+
+    if !version_found {
+        return Err("No version line to rewrite".to_string());
+    }
+    if let Some((os, arch)) = digests.keys().find(|key| !platforms_found.contains(*key)) {
+        return Err(format!(
+            "No digest line to rewrite for {}/{}, write platforms one key per line",
+            os, arch
+        ));
+    }
+    Ok(rewritten)
+}
+
+/// The new value of the digest `field` of a platform, whose value was `old`.
+fn new_digest(field: &str, old: &str, digests: &[Digest]) -> Option<String> {
+    let find = |algorithm: Algorithm| digests.iter().find(|digest| digest.algorithm == algorithm);
+    match field {
+        "digest" => Digest::parse(unquote(old))
+            .ok()
+            .and_then(|old| find(old.algorithm))
+            .map(Digest::to_string),
+        _ => Algorithm::from_name(field)
+            .and_then(find)
+            .map(|digest| digest.hex.clone()),
+    }
+}
+
+/// Splits a `key: value` line into its unquoted key and whatever follows the
+/// colon, including any comment.
+fn key_value(line: &str) -> Option<(String, &str)> {
+    if line.starts_with('#') || line.starts_with('-') {
+        return None;
+    }
+    let (key, value) = match line.split_once(": ") {
+        Some((key, value)) => (key, value),
+        None => (line.trim_end().strip_suffix(':')?, ""),
+    };
+    Some((unquote(key.trim()).to_string(), value))
+}
+
+/// The value of a line, without its comment or line break.
+fn scalar(value: &str) -> &str {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return "";
+    }
+    if let Some(quote @ ('"' | '\'')) = value.chars().next()
+        && let Some(end) = value[1..].find(quote)
+    {
+        return &value[..end + 2];
+    }
+    match value.find(" #") {
+        Some(index) => value[..index].trim_end(),
+        None => value,
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .or_else(|| {
+            text.strip_prefix('\'')
+                .and_then(|text| text.strip_suffix('\''))
+        })
+        .unwrap_or(text)
+}
+
+/// `line` with its value replaced by `replacement`, quoted the same way,
+/// keeping the comment and line break that follow.
+fn replace_value(line: &str, replacement: &str) -> String {
+    let (key, value) = match line.split_once(": ") {
+        Some(split) => split,
+        None => return line.to_string(),
+    };
+    let old = scalar(value);
+    let start = value.find(old).unwrap_or_default();
+    let quoted = match old.chars().next() {
+        Some(quote @ ('"' | '\'')) => format!("{}{}{}", quote, replacement, quote),
+        // A bare version such as 1.10 would read back as a number.
+        _ if replacement.parse::<f64>().is_ok() => format!("\"{}\"", replacement),
+        _ => replacement.to_string(),
+    };
+    format!(
+        "{}: {}{}{}",
+        key,
+        &value[..start],
+        quoted,
+        &value[start + old.len()..]
+    )
+}
+
+/// The lines of context around each change in [`diff`].
+const DIFF_CONTEXT: usize = 3;
+
+/// A unified diff of `old` and `new`, which have the same lines but for
+/// rewritten values, that `patch` can apply.
+fn diff(path: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let changed: Vec<usize> = (0..old.len())
+        .filter(|index| old[*index] != new[*index])
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Changes whose context overlaps share a hunk.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(old.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", path, path);
+    for (start, end) in hunks {
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            end - start,
+            start + 1,
+            end - start
+        ));
+        let mut index = start;
+        while index < end {
+            if old[index] == new[index] {
+                diff.push_str(&format!(" {}\n", old[index]));
+                index += 1;
+                continue;
+            }
+            let run_end = (index..end)
+                .find(|line| old[*line] == new[*line])
+                .unwrap_or(end);
+            for line in &old[index..run_end] {
+                diff.push_str(&format!("-{}\n", line));
+            }
+            for line in &new[index..run_end] {
+                diff.push_str(&format!("+{}\n", line));
+            }
+            index = run_end;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::http_download::test_support::{digest_of, get_available_port};
+    use iron::{Iron, IronResult, Listening, Request, Response, status};
+
+    /// Serves `<version>/tool_<os>_<arch>` with the version and platform as
+    /// its content.
+    fn artifact_server() -> (Listening, u16) {
+        let port = get_available_port().expect("No available port found");
+        let handler = |req: &mut Request| -> IronResult<Response> {
+            let path = req.url.path().join("/");
+            match path.split_once("/tool_") {
+                Some((version, platform)) => Ok(Response::with((
+                    status::Ok,
+                    format!("{} {}", version, platform),
+                ))),
+                None => Ok(Response::with(status::NotFound)),
+            }
+        };
+        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+    }
+
+    fn configuration(port: u16) -> String {
+        format!(
+            r#"#!/usr/bin/env launcher
+# The tool, pinned for the whole team.
+name: tool
+version: "1.0.0" # bumped by hand
+urlPattern: http://127.0.0.1:{}/{{{{version}}}}/tool_{{{{os}}}}_{{{{arch}}}}
+platforms:
+    linux:
+        amd64:
+            sha256: {} # linux
+    darwin:
+        # Apple silicon only.
+        arm64:
+            digest: {}
+"#,
+            port,
+            "0".repeat(64),
+            Digest::new(Algorithm::Sha512, &"0".repeat(128)).unwrap()
+        )
+    }
+
+    #[tokio::test]
+    async fn test_bump_rehashes_every_platform_keeping_comments() {
+        // Arrange
+        let (mut server, port) = artifact_server();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tool");
+        let original = configuration(port);
+        tokio::fs::write(&path, &original).await.unwrap();
+        let args = |args: &[&str]| -> Vec<String> {
+            args.iter()
+                .map(|arg| arg.to_string())
+                .chain([path.display().to_string(), "1.1.0".to_string()])
+                .collect()
+        };
+
+        // Act
+        let dry_run = run(&args(&["--dry-run"])).await;
+        let unchanged = tokio::fs::read_to_string(&path).await.unwrap();
+        let result = run(&args(&[])).await;
+
+        // Assert
+        assert!(dry_run.is_ok(), "{:?}", dry_run.err());
+        assert_eq!(unchanged, original);
+        assert!(result.is_ok(), "{:?}", result.err());
+        let linux = digest_of(Algorithm::Sha256, b"1.1.0 linux_amd64");
+        let darwin = digest_of(Algorithm::Sha512, b"1.1.0 darwin_arm64");
+        let expected = original
+            .replace("\"1.0.0\"", "\"1.1.0\"")
+            .replace(&"0".repeat(128), &darwin.hex)
+            .replace(&"0".repeat(64), &linux.hex);
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), expected);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_quotes_numeric_versions_and_keeps_comments() {
+        // Arrange
+        let content =
+            "version: 1.9 # latest\nplatforms:\n  linux:\n    amd64:\n      sha256: 'aa'\n";
+        let digest = digest_of(Algorithm::Sha256, b"content");
+        let digests = Digests::from([(
+            ("linux".to_string(), "amd64".to_string()),
+            vec![digest.clone()],
+        )]);
+
+        // Act
+        let rewritten = rewrite(content, "1.10", &digests).unwrap();
+
+        // Assert
+        assert_eq!(
+            rewritten,
+            content
+                .replace("1.9", "\"1.10\"")
+                .replace("'aa'", &format!("'{}'", digest.hex))
+        );
+    }
+
+    #[test]
+    fn test_rewrite_fails_for_flow_style_platforms() {
+        // Arrange
+        let content = "version: 1.0.0\nplatforms:\n  linux: {amd64: {sha256: aa}}\n";
+        let digests = Digests::from([(
+            ("linux".to_string(), "amd64".to_string()),
+            vec![digest_of(Algorithm::Sha256, b"content")],
+        )]);
+
+        // Act
+        let result = rewrite(content, "1.1.0", &digests);
+
+        // Assert
+        let error = result.unwrap_err();
+        assert!(error.contains("linux/amd64"), "{}", error);
+    }
+
+    #[test]
+    fn test_diff_shows_changed_lines() {
+        // Act
+        let diff = diff(
+            "tool.yaml",
+            "name: tool\nversion: 1.0.0\n",
+            "name: tool\nversion: 1.1.0\n",
+        );
+
+        // Assert
+        assert_eq!(
+            diff,
+            "--- tool.yaml\n+++ tool.yaml\n@@ -1,2 +1,2 @@\n name: tool\n-version: 1.0.0\n+version: 1.1.0\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_groups_nearby_changes_with_context() {
+        // Arrange
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no\np\n";
+        let new = "a\nB\nC\nd\ne\nf\ng\nH\ni\nj\nk\nl\nm\nn\no\nP\n";
+
+        // Act
+        let diff = diff("tool.yaml", old, new);
+
+        // Assert
+        assert_eq!(
+            diff,
+            "--- tool.yaml\n+++ tool.yaml\n\
+@@ -1,11 +1,11 @@\n a\n-b\n-c\n+B\n+C\n d\n e\n f\n g\n-h\n+H\n i\n j\n k\n\
+@@ -13,4 +13,4 @@\n m\n n\n o\n-p\n+P\n"
+        );
+    }
+}
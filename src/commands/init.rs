@@ -0,0 +1,280 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::Arguments;
+use crate::credentials::CredentialSources;
+use crate::digest::Algorithm;
+use crate::models::configuration::{
+    Compression, CompressionType, File, PlatformOSArchitecture, Platforms, RetryConfiguration,
+    Source,
+};
+use crate::providers::authenticated_downloader::authorize;
+use crate::providers::extractor::make_executable;
+use crate::providers::http_download;
+use crate::template;
+
+static USAGE: &str = "Usage: launcher init --name <name> --version <version> \
+--url-pattern <pattern> --platform <os/arch>... [--output <path>] [--force]";
+static SHEBANG: &str = "#!/usr/bin/env launcher";
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let arguments = Arguments::parse(args, &["force"])?;
+    let (Some(name), Some(version), Some(url_pattern)) = (
+        arguments.value("name"),
+        arguments.value("version"),
+        arguments.value("url-pattern"),
+    ) else {
+        return Err(USAGE.into());
+    };
+    let platforms = arguments.values("platform");
+    if platforms.is_empty() {
+        return Err(USAGE.into());
+    }
+    let output = arguments
+        .value("output")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.yaml", name)));
+
+    let configuration = generate(name, version, url_pattern, platforms).await?;
+    write(&configuration, &output, arguments.is_set("force")).await?;
+    eprintln!("Wrote {}", output.display());
+    Ok(())
+}
+
+/// Builds the configuration of the artifacts `url_pattern` expands to for
+/// each of `platforms`, given as `os/arch`, downloading each one to hash it.
+pub async fn generate(
+    name: &str,
+    version: &str,
+    url_pattern: &str,
+    platforms: &[String],
+) -> Result<File, Box<dyn std::error::Error>> {
+    let client = http_download::client();
+    let credentials = CredentialSources::from_environment();
+    let mut compression_type = None;
+    let mut digests = Platforms::new();
+    for platform in platforms {
+        let (os, arch) = platform
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid platform {}, expected os/arch", platform))?;
+        let url = template::render(url_pattern, version, os, arch);
+
+        let detected = detect_compression(&url);
+        match compression_type {
+            Some(previous) if previous != detected => {
+                return Err(format!(
+                    "The artifacts are compressed differently, {:?} and {:?}",
+                    previous, detected
+                )
+                .into());
+            }
+            _ => compression_type = Some(detected),
+        }
+
+        let credential = credentials.resolve(None, &url).await?;
+        eprintln!("Hashing {}...", url);
+        let digest = http_download::fetch_digest(&client, &url, Algorithm::Sha256, &|request| {
+            match &credential {
+                Some(credential) => authorize(request, credential),
+                None => request,
+            }
+        })
+        .await?;
+        digests.entry(os.to_string()).or_default().insert(
+            arch.to_string(),
+            PlatformOSArchitecture {
+                sha256: Some(digest.hex),
+                sha512: None,
+                blake3: None,
+                digest: None,
+                executable: None,
+            },
+        );
+    }
+
+    Ok(File {
+        name: name.to_string(),
+        version: version.to_string(),
+        source: Source::Url,
+        url_pattern: url_pattern.to_string(),
+        repo: None,
+        asset: None,
+        tag: None,
+        api_url: None,
+        platforms: digests,
+        checksums_url: None,
+        checksums_algorithm: None,
+        checksums_signature: None,
+        signature: None,
+        versions: None,
+        strip_prefix: None,
+        executable: None,
+        entrypoints: Default::default(),
+        compression: Compression {
+            compression_type: compression_type.unwrap_or_default(),
+        },
+        retry: RetryConfiguration::default(),
+        credential: None,
+    })
+}
+
+/// The compression of the artifact at `url`, told by its extension.
+fn detect_compression(url: &str) -> CompressionType {
+    let path = reqwest::Url::parse(url)
+        .map(|url| url.path().to_lowercase())
+        .unwrap_or_else(|_| url.to_lowercase());
+    [
+        (".tar.gz", CompressionType::TarGz),
+        (".tgz", CompressionType::TarGz),
+        (".tar.xz", CompressionType::TarXz),
+        (".txz", CompressionType::TarXz),
+        (".tar.bz2", CompressionType::TarBz2),
+        (".tbz2", CompressionType::TarBz2),
+        (".tar.zst", CompressionType::TarZst),
+        (".tzst", CompressionType::TarZst),
+        (".zip", CompressionType::Zip),
+        (".gz", CompressionType::Gzip),
+    ]
+    .into_iter()
+    .find(|(extension, _)| path.ends_with(extension))
+    .map_or(CompressionType::None, |(_, compression_type)| {
+        compression_type
+    })
+}
+
+/// Writes `configuration` to `path` behind a `#!` line, so that it runs as
+/// the tool itself.
+async fn write(
+    configuration: &File,
+    path: &Path,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if tokio::fs::symlink_metadata(path).await.is_ok() && !force {
+        return Err(format!(
+            "Refusing to overwrite {}, it already exists (use --force)",
+            path.display()
+        )
+        .into());
+    }
+    let content = format!("{}\n{}", SHEBANG, serde_yaml::to_string(configuration)?);
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| format!("Failed to write file {}, {}", path.display(), e))?;
+    make_executable(path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::http_download::test_support::{get_available_port, sha256_of};
+    use iron::{Iron, IronResult, Listening, Request, Response, status};
+
+    /// Serves `tool_<os>_<arch>.tar.gz` with the platform as its content.
+    fn artifact_server() -> (Listening, u16) {
+        let port = get_available_port().expect("No available port found");
+        let handler = |req: &mut Request| -> IronResult<Response> {
+            let name = req.url.path().join("/");
+            match name
+                .strip_prefix("1.0.0/tool_")
+                .and_then(|name| name.strip_suffix(".tar.gz"))
+            {
+                Some(platform) => Ok(Response::with((status::Ok, platform.to_string()))),
+                None => Ok(Response::with(status::NotFound)),
+            }
+        };
+        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+    }
+
+    #[tokio::test]
+    async fn test_generate_hashes_every_platform() {
+        // Arrange
+        let (mut server, port) = artifact_server();
+        let url_pattern = format!(
+            "http://127.0.0.1:{}/{{{{version}}}}/tool_{{{{os}}}}_{{{{arch}}}}.tar.gz",
+            port
+        );
+        let platforms = vec!["linux/amd64".to_string(), "darwin/arm64".to_string()];
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = temp_dir.path().join("tool");
+
+        // Act
+        let configuration = generate("tool", "1.0.0", &url_pattern, &platforms)
+            .await
+            .unwrap();
+        write(&configuration, &output, false).await.unwrap();
+
+        // Assert
+        let content = tokio::fs::read_to_string(&output).await.unwrap();
+        assert!(
+            content.starts_with("#!/usr/bin/env launcher\n"),
+            "{}",
+            content
+        );
+        let parsed: File = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(parsed.name, "tool");
+        assert_eq!(parsed.url_pattern, url_pattern);
+        assert!(matches!(
+            parsed.compression.compression_type,
+            CompressionType::TarGz
+        ));
+        assert_eq!(
+            parsed.platforms["linux"]["amd64"].digest().unwrap(),
+            Some(sha256_of(b"linux_amd64"))
+        );
+        assert_eq!(
+            parsed.platforms["darwin"]["arm64"].digest().unwrap(),
+            Some(sha256_of(b"darwin_arm64"))
+        );
+        assert!(write(&configuration, &output, false).await.is_err());
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_generate_fails_for_missing_artifact_or_invalid_platform() {
+        // Arrange
+        let (mut server, port) = artifact_server();
+        let url_pattern = format!(
+            "http://127.0.0.1:{}/{{{{version}}}}/tool_{{{{os}}}}_{{{{arch}}}}.tar.gz",
+            port
+        );
+
+        // Act
+        let missing = generate("tool", "2.0.0", &url_pattern, &["linux/amd64".to_string()]).await;
+        let invalid = generate("tool", "1.0.0", &url_pattern, &["linux".to_string()]).await;
+
+        // Assert
+        let error = missing.err().unwrap().to_string();
+        assert!(error.contains("server returned 404"), "{}", error);
+        let error = invalid.err().unwrap().to_string();
+        assert_eq!(error, "Invalid platform linux, expected os/arch");
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[test]
+    fn test_detect_compression_from_extension() {
+        assert_eq!(
+            detect_compression("https://example.com/tool_1.0.0_linux_amd64.zip"),
+            CompressionType::Zip
+        );
+        assert_eq!(
+            detect_compression("https://example.com/tool-1.0.0.TGZ?download=1"),
+            CompressionType::TarGz
+        );
+        assert_eq!(
+            detect_compression("https://example.com/tool.tar.zst"),
+            CompressionType::TarZst
+        );
+        assert_eq!(
+            detect_compression("https://example.com/tool.gz"),
+            CompressionType::Gzip
+        );
+        assert_eq!(
+            detect_compression("https://example.com/tool-linux-amd64"),
+            CompressionType::None
+        );
+    }
+}
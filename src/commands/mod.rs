@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+pub mod bump;
+pub mod init;
+pub mod shim;
+
+/// Flags and positional arguments of a subcommand. Options take a value
+/// (`--name value` or `--name=value`) and may repeat; switches do not.
+#[derive(Debug, Default)]
+pub struct Arguments {
+    pub positional: Vec<String>,
+    options: BTreeMap<String, Vec<String>>,
+    switches: BTreeSet<String>,
+}
+
+impl Arguments {
+    pub fn parse(args: &[String], switches: &[&str]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut parsed = Arguments::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            if let Some((name, value)) = flag.split_once('=') {
+                parsed
+                    .options
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value.to_string());
+            } else if switches.contains(&flag) {
+                parsed.switches.insert(flag.to_string());
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for --{}", flag))?;
+                parsed
+                    .options
+                    .entry(flag.to_string())
+                    .or_default()
+                    .push(value.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    pub fn values(&self, name: &str) -> &[String] {
+        self.options.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_set(&self, switch: &str) -> bool {
+        self.switches.contains(switch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options_switches_and_positional() {
+        let parsed = Arguments::parse(
+            &args(&[
+                "a.yaml",
+                "--platform",
+                "linux/amd64",
+                "--platform=darwin/arm64",
+                "--force",
+                "b.yaml",
+            ]),
+            &["force"],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.positional, args(&["a.yaml", "b.yaml"]));
+        assert_eq!(
+            parsed.values("platform"),
+            args(&["linux/amd64", "darwin/arm64"])
+        );
+        assert_eq!(parsed.value("platform"), Some("darwin/arm64"));
+        assert!(parsed.is_set("force"));
+        assert!(!parsed.is_set("dry-run"));
+        assert_eq!(parsed.value("name"), None);
+    }
+
+    #[test]
+    fn test_parse_missing_option_value() {
+        assert!(Arguments::parse(&args(&["--bin-dir"]), &[]).is_err());
+    }
+}
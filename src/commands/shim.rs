@@ -0,0 +1,649 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::Arguments;
+use crate::invocation::find_tool_configuration;
+use crate::providers::extractor::make_executable;
+
+static USAGE: &str =
+    "Usage: launcher shim install [--bin-dir <dir>] [--symlink] [--force] <config>...
+       launcher shim list [--bin-dir <dir>]
+       launcher shim remove [--bin-dir <dir>] <name>...";
+pub static DEFAULT_BIN_LOCATION: &str = "~/.local/bin";
+static SHIM_MARKER: &str = "# Generated by `launcher shim install` from ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShimMode {
+    /// A copy of the configuration with a `#!` line pointing at the launcher.
+    Shebang,
+    /// A symlink to the launcher, with the configuration linked into the tools
+    /// directory so argv[0] dispatch can find it.
+    Symlink,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Shim {
+    pub name: String,
+    pub path: PathBuf,
+    pub mode: ShimMode,
+    /// The configuration a shebang shim was generated from, or the launcher a
+    /// symlink shim points at.
+    pub source: PathBuf,
+}
+
+pub async fn run(args: &[String], tools_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (subcommand, rest) = args.split_first().ok_or(USAGE)?;
+    let arguments = Arguments::parse(rest, &["symlink", "force"])?;
+    let bin_location = arguments.value("bin-dir").unwrap_or(DEFAULT_BIN_LOCATION);
+    let bin_dir = PathBuf::from(shellexpand::tilde(bin_location).as_ref());
+    let launcher = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the launcher executable, {}", e))?;
+
+    match subcommand.as_str() {
+        "install" => {
+            if arguments.positional.is_empty() {
+                return Err(USAGE.into());
+            }
+            let mode = if arguments.is_set("symlink") {
+                ShimMode::Symlink
+            } else {
+                ShimMode::Shebang
+            };
+            let config_paths: Vec<PathBuf> =
+                arguments.positional.iter().map(PathBuf::from).collect();
+            let shims = install(
+                &config_paths,
+                &bin_dir,
+                tools_dir,
+                &launcher,
+                mode,
+                arguments.is_set("force"),
+            )
+            .await?;
+            for shim in shims {
+                eprintln!("Installed {}", shim.path.display());
+            }
+        }
+        "list" => {
+            for shim in list(&bin_dir, &launcher).await? {
+                println!("{}\t{}", shim.name, shim.source.display());
+            }
+        }
+        "remove" => {
+            if arguments.positional.is_empty() {
+                return Err(USAGE.into());
+            }
+            for path in remove(&arguments.positional, &bin_dir, tools_dir, &launcher).await? {
+                eprintln!("Removed {}", path.display());
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+/// Writes one shim per tool: the configuration's `name` plus every entrypoint.
+pub async fn install(
+    config_paths: &[PathBuf],
+    bin_dir: &Path,
+    tools_dir: &Path,
+    launcher: &Path,
+    mode: ShimMode,
+    force: bool,
+) -> Result<Vec<Shim>, Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(bin_dir)
+        .await
+        .map_err(|e| format!("Failed to create dir {}, {}", bin_dir.display(), e))?;
+
+    let mut installed = Vec::new();
+    for config_path in config_paths {
+        let config_path = tokio::fs::canonicalize(config_path).await.map_err(|e| {
+            format!(
+                "Failed to read configuration {}, {}",
+                config_path.display(),
+                e
+            )
+        })?;
+        let configuration = crate::read_configuration(&config_path).await?;
+
+        let mut names = vec![configuration.name.clone()];
+        names.extend(
+            configuration
+                .entrypoints
+                .keys()
+                .filter(|name| **name != configuration.name)
+                .cloned(),
+        );
+
+        if mode == ShimMode::Symlink {
+            tokio::fs::create_dir_all(tools_dir)
+                .await
+                .map_err(|e| format!("Failed to create dir {}, {}", tools_dir.display(), e))?;
+            let tools_link = tools_dir.join(format!("{}.yaml", configuration.name));
+            replace_with_symlink(&config_path, &tools_link, force).await?;
+        }
+
+        for name in names {
+            let path = bin_dir.join(&name);
+            if tokio::fs::symlink_metadata(&path).await.is_ok() {
+                if inspect(&path, launcher).await.is_none() && !force {
+                    return Err(format!(
+                        "Refusing to overwrite {}, it is not a launcher shim (use --force)",
+                        path.display()
+                    )
+                    .into());
+                }
+                tokio::fs::remove_file(&path)
+                    .await
+                    .map_err(|e| format!("Failed to remove file {}, {}", path.display(), e))?;
+            }
+
+            let source = match mode {
+                ShimMode::Shebang => {
+                    let content = tokio::fs::read_to_string(&config_path).await?;
+                    let shim =
+                        shebang_shim(&launcher.display().to_string(), &config_path, &content);
+                    tokio::fs::write(&path, shim)
+                        .await
+                        .map_err(|e| format!("Failed to write file {}, {}", path.display(), e))?;
+                    make_executable(&path).await?;
+                    config_path.clone()
+                }
+                ShimMode::Symlink => {
+                    tokio::fs::symlink(launcher, &path).await.map_err(|e| {
+                        format!("Failed to create symlink {}, {}", path.display(), e)
+                    })?;
+                    launcher.to_path_buf()
+                }
+            };
+            installed.push(Shim {
+                name,
+                path,
+                mode,
+                source,
+            });
+        }
+    }
+    Ok(installed)
+}
+
+/// Rewrites the shebang shims in `bin_dir` generated from `config_path` with
+/// its current content, as they are copies of it, keeping their `#!` line.
+/// Returns the paths that were rewritten.
+pub async fn refresh(
+    config_path: &Path,
+    bin_dir: &Path,
+    launcher: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let config_path = tokio::fs::canonicalize(config_path).await.map_err(|e| {
+        format!(
+            "Failed to read configuration {}, {}",
+            config_path.display(),
+            e
+        )
+    })?;
+    let content = tokio::fs::read_to_string(&config_path)
+        .await
+        .map_err(|e| format!("Failed to read file {}, {}", config_path.display(), e))?;
+
+    let mut refreshed = Vec::new();
+    for shim in list(bin_dir, launcher).await? {
+        if shim.mode != ShimMode::Shebang || shim.source != config_path {
+            continue;
+        }
+        let current = tokio::fs::read_to_string(&shim.path)
+            .await
+            .map_err(|e| format!("Failed to read file {}, {}", shim.path.display(), e))?;
+        let interpreter = current
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("#!"))
+            .unwrap_or_default();
+        tokio::fs::write(
+            &shim.path,
+            shebang_shim(interpreter, &config_path, &content),
+        )
+        .await
+        .map_err(|e| format!("Failed to write file {}, {}", shim.path.display(), e))?;
+        refreshed.push(shim.path);
+    }
+    Ok(refreshed)
+}
+
+/// A copy of the configuration `content` read from `config_path`, with its
+/// own `#!` line replaced by one running `interpreter`.
+fn shebang_shim(interpreter: &str, config_path: &Path, content: &str) -> String {
+    let body = match content.strip_prefix("#!") {
+        Some(rest) => rest.split_once('\n').map_or("", |(_, body)| body),
+        None => content,
+    };
+    format!(
+        "#!{}\n{}{}\n{}",
+        interpreter,
+        SHIM_MARKER,
+        config_path.display(),
+        body
+    )
+}
+
+/// Lists the launcher shims in `bin_dir`, ignoring anything else living there.
+pub async fn list(
+    bin_dir: &Path,
+    launcher: &Path,
+) -> Result<Vec<Shim>, Box<dyn std::error::Error>> {
+    let mut shims = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(bin_dir).await else {
+        return Ok(shims);
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(shim) = inspect(&entry.path(), launcher).await {
+            shims.push(shim);
+        }
+    }
+    shims.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(shims)
+}
+
+/// Removes the named shims along with the other shims of their
+/// configuration, so that none is left pointing at nothing, and the tools
+/// directory link of symlink shims. Returns the paths that were removed.
+pub async fn remove(
+    names: &[String],
+    bin_dir: &Path,
+    tools_dir: &Path,
+    launcher: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut removed = Vec::new();
+    for name in names {
+        let path = bin_dir.join(name);
+        if removed.contains(&path) {
+            continue;
+        }
+        let shim = inspect(&path, launcher)
+            .await
+            .ok_or_else(|| format!("{} is not a launcher shim", path.display()))?;
+
+        let mut paths = vec![shim.path.clone()];
+        match shim.mode {
+            ShimMode::Shebang => {
+                for other in list(bin_dir, launcher).await? {
+                    if other.mode == ShimMode::Shebang && other.source == shim.source {
+                        paths.push(other.path);
+                    }
+                }
+            }
+            ShimMode::Symlink => {
+                // Only a configuration that `install` linked goes away with
+                // the shim, and the other shims resolving to it with it.
+                if let Ok(tools_link) = find_tool_configuration(tools_dir, name).await
+                    && tokio::fs::symlink_metadata(&tools_link)
+                        .await
+                        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+                {
+                    if let Ok(configuration) = crate::read_configuration(&tools_link).await {
+                        let names = std::iter::once(&configuration.name)
+                            .chain(configuration.entrypoints.keys());
+                        for name in names {
+                            let path = bin_dir.join(name);
+                            if inspect(&path, launcher)
+                                .await
+                                .is_some_and(|other| other.mode == ShimMode::Symlink)
+                            {
+                                paths.push(path);
+                            }
+                        }
+                    }
+                    paths.push(tools_link);
+                }
+            }
+        }
+
+        for path in paths {
+            if removed.contains(&path) {
+                continue;
+            }
+            tokio::fs::remove_file(&path)
+                .await
+                .map_err(|e| format!("Failed to remove file {}, {}", path.display(), e))?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+async fn inspect(path: &Path, launcher: &Path) -> Option<Shim> {
+    let name = path.file_name()?.to_str()?.to_string();
+    let metadata = tokio::fs::symlink_metadata(path).await.ok()?;
+    if metadata.file_type().is_symlink() {
+        let target = tokio::fs::canonicalize(path).await.ok()?;
+        let launcher = tokio::fs::canonicalize(launcher).await.ok()?;
+        return (target == launcher).then(|| Shim {
+            name,
+            path: path.to_path_buf(),
+            mode: ShimMode::Symlink,
+            source: launcher,
+        });
+    }
+    if !metadata.is_file() {
+        return None;
+    }
+
+    let content = tokio::fs::read(path).await.ok()?;
+    let mut lines = content.split(|byte| *byte == b'\n');
+    if !lines.next()?.starts_with(b"#!") {
+        return None;
+    }
+    let source = std::str::from_utf8(lines.next()?)
+        .ok()?
+        .strip_prefix(SHIM_MARKER)?;
+    Some(Shim {
+        name,
+        path: path.to_path_buf(),
+        mode: ShimMode::Shebang,
+        source: PathBuf::from(source),
+    })
+}
+
+async fn replace_with_symlink(
+    target: &Path,
+    link: &Path,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Ok(metadata) = tokio::fs::symlink_metadata(link).await {
+        if !metadata.file_type().is_symlink() && !force {
+            return Err(format!(
+                "Refusing to overwrite {}, it is not a symlink (use --force)",
+                link.display()
+            )
+            .into());
+        }
+        tokio::fs::remove_file(link)
+            .await
+            .map_err(|e| format!("Failed to remove file {}, {}", link.display(), e))?;
+    }
+    tokio::fs::symlink(target, link)
+        .await
+        .map_err(|e| format!("Failed to create symlink {}, {}", link.display(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::{TempDir, tempdir};
+
+    static CONFIGURATION: &str = "#! /usr/bin/launcher
+name: go
+version: 1.24.0
+urlPattern: https://example.com/go{{version}}.{{os}}-{{arch}}.tar.gz
+executable: bin/go
+entrypoints:
+    gofmt: bin/gofmt
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+";
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        config_path: PathBuf,
+        bin_dir: PathBuf,
+        tools_dir: PathBuf,
+        launcher: PathBuf,
+    }
+
+    async fn fixture() -> Fixture {
+        let temp_dir = tempdir().unwrap();
+        let root = tokio::fs::canonicalize(temp_dir.path()).await.unwrap();
+        let config_path = root.join("go.yaml");
+        tokio::fs::write(&config_path, CONFIGURATION).await.unwrap();
+        let launcher = root.join("launcher");
+        tokio::fs::write(&launcher, b"binary").await.unwrap();
+        Fixture {
+            config_path,
+            bin_dir: root.join("bin"),
+            tools_dir: root.join("tools"),
+            launcher,
+            _temp_dir: temp_dir,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_shebang_shims_for_name_and_entrypoints() {
+        // Arrange
+        let fixture = fixture().await;
+
+        // Act
+        let shims = install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Shebang,
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        let names: Vec<_> = shims.iter().map(|shim| shim.name.as_str()).collect();
+        assert_eq!(names, vec!["go", "gofmt"]);
+
+        let content = tokio::fs::read_to_string(fixture.bin_dir.join("gofmt"))
+            .await
+            .unwrap();
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("#!{}", fixture.launcher.display())
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("{}{}", SHIM_MARKER, fixture.config_path.display())
+        );
+        assert_eq!(lines.next().unwrap(), "name: go");
+        let parsed: crate::models::configuration::File = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(parsed.name, "go");
+
+        let mode = std::fs::metadata(fixture.bin_dir.join("go"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_install_symlink_shims_link_launcher_and_tools_config() {
+        // Arrange
+        let fixture = fixture().await;
+
+        // Act
+        install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Symlink,
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        let shim_target = tokio::fs::read_link(fixture.bin_dir.join("gofmt"))
+            .await
+            .unwrap();
+        assert_eq!(shim_target, fixture.launcher);
+        let tools_target = tokio::fs::read_link(fixture.tools_dir.join("go.yaml"))
+            .await
+            .unwrap();
+        assert_eq!(tools_target, fixture.config_path);
+    }
+
+    #[tokio::test]
+    async fn test_install_refuses_to_overwrite_other_files() {
+        // Arrange
+        let fixture = fixture().await;
+        tokio::fs::create_dir_all(&fixture.bin_dir).await.unwrap();
+        tokio::fs::write(fixture.bin_dir.join("go"), b"#!/bin/sh\necho hi\n")
+            .await
+            .unwrap();
+
+        // Act
+        let result = install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Shebang,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert!(result.is_err());
+        let content = tokio::fs::read_to_string(fixture.bin_dir.join("go"))
+            .await
+            .unwrap();
+        assert_eq!(content, "#!/bin/sh\necho hi\n");
+    }
+
+    #[tokio::test]
+    async fn test_list_and_remove_only_touch_shims() {
+        // Arrange
+        let fixture = fixture().await;
+        install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Symlink,
+            false,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(fixture.bin_dir.join("other"), b"#!/bin/sh\n")
+            .await
+            .unwrap();
+
+        // Act
+        let listed = list(&fixture.bin_dir, &fixture.launcher).await.unwrap();
+        let removed = remove(
+            &["go".to_string()],
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+        )
+        .await
+        .unwrap();
+        let refused = remove(
+            &["other".to_string()],
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+        )
+        .await;
+
+        // Assert
+        let names: Vec<_> = listed.iter().map(|shim| shim.name.as_str()).collect();
+        assert_eq!(names, vec!["go", "gofmt"]);
+        assert_eq!(
+            removed,
+            vec![
+                fixture.bin_dir.join("go"),
+                fixture.bin_dir.join("gofmt"),
+                fixture.tools_dir.join("go.yaml")
+            ]
+        );
+        assert!(refused.is_err());
+        assert!(
+            tokio::fs::symlink_metadata(fixture.bin_dir.join("gofmt"))
+                .await
+                .is_err()
+        );
+        assert!(fixture.bin_dir.join("other").exists());
+    }
+
+    #[tokio::test]
+    async fn test_remove_entrypoint_removes_shebang_shims_of_its_configuration() {
+        // Arrange
+        let fixture = fixture().await;
+        install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Shebang,
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let removed = remove(
+            &["gofmt".to_string(), "go".to_string()],
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            removed,
+            vec![fixture.bin_dir.join("gofmt"), fixture.bin_dir.join("go")]
+        );
+        assert!(
+            list(&fixture.bin_dir, &fixture.launcher)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rewrites_shebang_shims_of_configuration() {
+        // Arrange
+        let fixture = fixture().await;
+        install(
+            std::slice::from_ref(&fixture.config_path),
+            &fixture.bin_dir,
+            &fixture.tools_dir,
+            &fixture.launcher,
+            ShimMode::Shebang,
+            false,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            &fixture.config_path,
+            CONFIGURATION.replace("1.24.0", "1.25.0"),
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let refreshed = refresh(&fixture.config_path, &fixture.bin_dir, &fixture.launcher)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            refreshed,
+            vec![fixture.bin_dir.join("go"), fixture.bin_dir.join("gofmt")]
+        );
+        let content = tokio::fs::read_to_string(fixture.bin_dir.join("gofmt"))
+            .await
+            .unwrap();
+        assert!(content.starts_with(&format!(
+            "#!{}\n{}{}\n",
+            fixture.launcher.display(),
+            SHIM_MARKER,
+            fixture.config_path.display()
+        )));
+        let parsed: crate::models::configuration::File = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(parsed.version, "1.25.0");
+    }
+}
@@ -0,0 +1,961 @@
+//! Offline verification of cosign bundles of keyless signatures: the
+//! signing certificate must chain to a configured Fulcio root and name the
+//! expected identity, and the entry timestamp of Rekor stands in for querying
+//! the log.
+
+use std::io::Read;
+
+use base64::Engine;
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::verify::X509VerifyParam;
+use openssl::x509::{X509, X509StoreContext};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use sha2::Digest as _;
+use x509_cert::Certificate;
+use x509_cert::der::asn1::ObjectIdentifier;
+use x509_cert::der::{Decode, DecodePem, Encode};
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::{BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAltName};
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+
+use crate::models::configuration::{Signature, TrustRoot};
+use crate::models::cosign::{Bundle, HashedRekord};
+use crate::signature;
+
+const SECP256R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const SECP384R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+const KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.15");
+const SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+const BASIC_CONSTRAINTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.19");
+const EXTENDED_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37");
+const CODE_SIGNING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.3");
+/// The OIDC issuer, as a DER `UTF8String`.
+const FULCIO_ISSUER: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.8");
+/// The OIDC issuer, as raw bytes, in certificates Fulcio issued until 2023.
+const FULCIO_ISSUER_V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.1");
+
+/// What a bundle holds, whatever its format.
+struct Entry {
+    certificate: Vec<u8>,
+    /// Certificates the bundle gives to chain the signing one to the trust
+    /// root, which are not trusted by themselves.
+    intermediates: Vec<Vec<u8>>,
+    signature: Vec<u8>,
+    /// The Rekor entry, in base64, as the entry timestamp signs it.
+    body: String,
+    integrated_time: i64,
+    log_index: i64,
+    /// SHA-256 of the public key of the log, in hex.
+    log_id: String,
+    signed_entry_timestamp: Vec<u8>,
+    /// The SHA-256 of the content the bundle claims to sign, if it says.
+    message_digest: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_name(name: &str) -> Option<HashAlgorithm> {
+        match name {
+            "sha256" => Some(HashAlgorithm::Sha256),
+            "sha384" => Some(HashAlgorithm::Sha384),
+            "sha512" => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    fn hasher(self) -> Box<dyn sha2::digest::DynDigest> {
+        match self {
+            HashAlgorithm::Sha256 => Box::new(sha2::Sha256::new()),
+            HashAlgorithm::Sha384 => Box::new(sha2::Sha384::new()),
+            HashAlgorithm::Sha512 => Box::new(sha2::Sha512::new()),
+        }
+    }
+
+    fn hash(self, content: &[u8]) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(content);
+        hasher.finalize().to_vec()
+    }
+}
+
+/// The digests of the signed content, read once for every algorithm the
+/// bundle needs.
+struct ContentDigests(Vec<(HashAlgorithm, Vec<u8>)>);
+
+impl ContentDigests {
+    fn read(content: impl Read, algorithms: &[HashAlgorithm]) -> Result<ContentDigests, String> {
+        let mut hashers: Vec<(HashAlgorithm, Box<dyn sha2::digest::DynDigest>)> = Vec::new();
+        for algorithm in algorithms {
+            if !hashers.iter().any(|(listed, _)| listed == algorithm) {
+                hashers.push((*algorithm, algorithm.hasher()));
+            }
+        }
+        signature::read_chunks(content, |chunk| {
+            for (_, hasher) in &mut hashers {
+                hasher.update(chunk);
+            }
+        })?;
+        Ok(ContentDigests(
+            hashers
+                .into_iter()
+                .map(|(algorithm, hasher)| (algorithm, hasher.finalize().to_vec()))
+                .collect(),
+        ))
+    }
+
+    fn get(&self, algorithm: HashAlgorithm) -> &[u8] {
+        self.0
+            .iter()
+            .find(|(listed, _)| *listed == algorithm)
+            .map_or(&[], |(_, digest)| digest.as_slice())
+    }
+}
+
+enum PublicKey {
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+}
+
+impl PublicKey {
+    fn from_spki(spki: &SubjectPublicKeyInfoOwned) -> Result<PublicKey, String> {
+        let point = spki.subject_public_key.raw_bytes();
+        let invalid = |e| format!("Invalid public key, {}", e);
+        let curve = spki
+            .algorithm
+            .parameters
+            .as_ref()
+            .and_then(|parameters| parameters.decode_as::<ObjectIdentifier>().ok());
+        match curve {
+            Some(SECP256R1) => p256::ecdsa::VerifyingKey::from_sec1_bytes(point)
+                .map(PublicKey::P256)
+                .map_err(invalid),
+            Some(SECP384R1) => p384::ecdsa::VerifyingKey::from_sec1_bytes(point)
+                .map(PublicKey::P384)
+                .map_err(invalid),
+            _ => Err("Unsupported public key, expected ECDSA P-256 or P-384".to_string()),
+        }
+    }
+
+    /// The hash signatures made with the key are computed with.
+    fn hash_algorithm(&self) -> HashAlgorithm {
+        match self {
+            PublicKey::P256(_) => HashAlgorithm::Sha256,
+            PublicKey::P384(_) => HashAlgorithm::Sha384,
+        }
+    }
+
+    /// Checks the DER `signature` of a message hashed into `prehash`.
+    fn verify(&self, prehash: &[u8], signature: &[u8]) -> bool {
+        match self {
+            PublicKey::P256(key) => p256::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(prehash, &signature).is_ok()),
+            PublicKey::P384(key) => p384::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(prehash, &signature).is_ok()),
+        }
+    }
+}
+
+/// Checks that the cosign `bundle` signs `content` with a certificate
+/// issued to the identity `configuration` expects, under its trust root.
+pub fn verify(configuration: &Signature, content: impl Read, bundle: &[u8]) -> Result<(), String> {
+    let (Some(identity), Some(issuer), Some(trust_root)) = (
+        &configuration.identity,
+        &configuration.issuer,
+        &configuration.trust_root,
+    ) else {
+        return Err("A cosign signature requires identity, issuer and trustRoot".to_string());
+    };
+    let bundle: Bundle =
+        serde_json::from_slice(bundle).map_err(|e| format!("Invalid cosign bundle, {}", e))?;
+    let entry = entry(bundle)?;
+    let certificate = Certificate::from_der(&entry.certificate)
+        .map_err(|e| format!("Invalid signing certificate, {}", e))?;
+
+    check_issued(
+        &certificate,
+        &entry.certificate,
+        &entry.intermediates,
+        trust_root,
+        entry.integrated_time,
+    )?;
+    check_identity(&certificate, identity, issuer)?;
+    let (logged_algorithm, logged_digest) = check_log_entry(&entry, trust_root)?;
+
+    let key = PublicKey::from_spki(&certificate.tbs_certificate.subject_public_key_info)?;
+    let digests = ContentDigests::read(
+        content,
+        &[
+            HashAlgorithm::Sha256,
+            key.hash_algorithm(),
+            logged_algorithm,
+        ],
+    )?;
+    if !logged_digest.eq_ignore_ascii_case(&hex(digests.get(logged_algorithm))) {
+        return Err("The transparency log entry is for another signature".to_string());
+    }
+    if let Some(digest) = &entry.message_digest
+        && digest != digests.get(HashAlgorithm::Sha256)
+    {
+        return Err("The bundle signs other content".to_string());
+    }
+    if !key.verify(digests.get(key.hash_algorithm()), &entry.signature) {
+        return Err("Signature does not match".to_string());
+    }
+    Ok(())
+}
+
+fn entry(bundle: Bundle) -> Result<Entry, String> {
+    match bundle {
+        Bundle::Sigstore(bundle) => {
+            let material = bundle.verification_material;
+            let mut chain = material
+                .x509_certificate_chain
+                .map(|chain| chain.certificates)
+                .unwrap_or_default();
+            let certificate = match material.certificate {
+                Some(certificate) => certificate,
+                None if !chain.is_empty() => chain.remove(0),
+                None => return Err("The bundle has no signing certificate".to_string()),
+            };
+            let log_entry = material
+                .tlog_entries
+                .into_iter()
+                .next()
+                .ok_or("The bundle has no transparency log entry")?;
+            let promise = log_entry
+                .inclusion_promise
+                .ok_or("The bundle has no signed entry timestamp")?;
+            let number = |value: &str| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid number {} in the bundle", value))
+            };
+            let message_digest = match bundle.message_signature.message_digest {
+                Some(digest) if digest.algorithm == "SHA2_256" => Some(base64(&digest.digest)?),
+                _ => None,
+            };
+            Ok(Entry {
+                certificate: base64(&certificate.raw_bytes)?,
+                intermediates: chain
+                    .iter()
+                    .map(|certificate| base64(&certificate.raw_bytes))
+                    .collect::<Result<_, _>>()?,
+                signature: base64(&bundle.message_signature.signature)?,
+                body: STANDARD.encode(base64(&log_entry.canonicalized_body)?),
+                integrated_time: number(&log_entry.integrated_time)?,
+                log_index: number(&log_entry.log_index)?,
+                log_id: hex(&base64(&log_entry.log_id.key_id)?),
+                signed_entry_timestamp: base64(&promise.signed_entry_timestamp)?,
+                message_digest,
+            })
+        }
+        Bundle::Legacy(bundle) => {
+            let payload = bundle.rekor_bundle.payload;
+            Ok(Entry {
+                certificate: certificate_der(&bundle.cert)?,
+                intermediates: Vec::new(),
+                signature: base64(&bundle.base64_signature)?,
+                body: payload.body,
+                integrated_time: payload.integrated_time,
+                log_index: payload.log_index,
+                log_id: payload.log_id,
+                signed_entry_timestamp: base64(&bundle.rekor_bundle.signed_entry_timestamp)?,
+                message_digest: None,
+            })
+        }
+    }
+}
+
+/// Checks that `certificate`, given in `der` too, chains to a self-signed
+/// root among the certificates of `trust_root`, through `intermediates` or
+/// the other certificates there. Every certificate of the path must be valid
+/// at `time`, and every issuer a certificate authority allowed to sign
+/// certificates.
+fn check_issued(
+    certificate: &Certificate,
+    der: &[u8],
+    intermediates: &[Vec<u8>],
+    trust_root: &TrustRoot,
+    time: i64,
+) -> Result<(), String> {
+    let validity = &certificate.tbs_certificate.validity;
+    let not_before = validity.not_before.to_unix_duration().as_secs() as i64;
+    let not_after = validity.not_after.to_unix_duration().as_secs() as i64;
+    if !(not_before..=not_after).contains(&time) {
+        return Err("The signing certificate had expired when the log recorded it".to_string());
+    }
+
+    let invalid_root = |e| format!("Invalid trustRoot certificates, {}", e);
+    let invalid = |e| format!("Invalid signing certificate, {}", e);
+    let mut store = X509StoreBuilder::new().map_err(invalid_root)?;
+    for authority in
+        X509::stack_from_pem(trust_root.certificates.as_bytes()).map_err(invalid_root)?
+    {
+        store.add_cert(authority).map_err(invalid_root)?;
+    }
+    let mut parameters = X509VerifyParam::new().map_err(invalid_root)?;
+    parameters.set_time(time as _);
+    store.set_param(&parameters).map_err(invalid_root)?;
+    let store = store.build();
+
+    let certificate = X509::from_der(der).map_err(invalid)?;
+    let mut chain = Stack::new().map_err(invalid)?;
+    for intermediate in intermediates {
+        chain
+            .push(X509::from_der(intermediate).map_err(invalid)?)
+            .map_err(invalid)?;
+    }
+    let mut context = X509StoreContext::new().map_err(invalid)?;
+    let result = context
+        .init(&store, &certificate, &chain, |context| {
+            Ok(if context.verify_cert()? {
+                Ok(())
+            } else {
+                Err(context.error())
+            })
+        })
+        .map_err(invalid)?;
+    result.map_err(|error| {
+        format!(
+            "The signing certificate does not chain to the trustRoot, {}",
+            error.error_string()
+        )
+    })
+}
+
+/// Checks that `certificate` is for code signing by `identity`, as vouched
+/// for by `issuer`.
+fn check_identity(certificate: &Certificate, identity: &str, issuer: &str) -> Result<(), String> {
+    check_identity_pattern(identity)?;
+    let extensions = certificate
+        .tbs_certificate
+        .extensions
+        .as_deref()
+        .unwrap_or_default();
+    let extension = |oid: ObjectIdentifier| {
+        extensions
+            .iter()
+            .find(|extension| extension.extn_id == oid)
+            .map(|extension| extension.extn_value.as_bytes())
+    };
+
+    let code_signing = extension(EXTENDED_KEY_USAGE)
+        .and_then(|value| ExtendedKeyUsage::from_der(value).ok())
+        .is_some_and(|usage| usage.0.contains(&CODE_SIGNING));
+    let digital_signature = extension(KEY_USAGE)
+        .and_then(|value| KeyUsage::from_der(value).ok())
+        .is_some_and(|usage| usage.digital_signature());
+    if !code_signing || !digital_signature {
+        return Err("The signing certificate is not for code signing".to_string());
+    }
+    if extension(BASIC_CONSTRAINTS)
+        .and_then(|value| BasicConstraints::from_der(value).ok())
+        .is_some_and(|constraints| constraints.ca)
+    {
+        return Err("The signing certificate is a certificate authority".to_string());
+    }
+
+    let names: Vec<String> = extension(SUBJECT_ALT_NAME)
+        .and_then(|value| SubjectAltName::from_der(value).ok())
+        .map(|names| {
+            names
+                .0
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::Rfc822Name(email) => Some(email.to_string()),
+                    GeneralName::UniformResourceIdentifier(uri) => Some(uri.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !names.iter().any(|name| identity_matches(identity, name)) {
+        return Err(format!(
+            "The signing certificate is issued to {}, not {}",
+            names.join(", "),
+            identity
+        ));
+    }
+
+    let certificate_issuer = extension(FULCIO_ISSUER)
+        .and_then(|value| String::from_der(value).ok())
+        .or_else(|| {
+            extension(FULCIO_ISSUER_V1).map(|value| String::from_utf8_lossy(value).to_string())
+        })
+        .ok_or("The signing certificate names no issuer")?;
+    if certificate_issuer != issuer {
+        return Err(format!(
+            "The signing certificate is vouched for by {}, not {}",
+            certificate_issuer, issuer
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects a `*` in `identity` anywhere but as its last path segment: a
+/// pattern such as `https://github.com/org*` would accept `org-evil` too.
+fn check_identity_pattern(identity: &str) -> Result<(), String> {
+    if identity
+        .strip_suffix("/*")
+        .unwrap_or(identity)
+        .contains('*')
+    {
+        return Err(format!(
+            "Identity {} may only use * as its last path segment",
+            identity
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `name` is `identity`, or is under it when it ends with a `/*`
+/// segment: `https://github.com/owner/tool/.github/workflows/release.yml@refs/tags/*`
+/// accepts that workflow for any tag.
+fn identity_matches(identity: &str, name: &str) -> bool {
+    match identity.strip_suffix('*') {
+        Some(prefix) if prefix.ends_with('/') => name.starts_with(prefix),
+        _ => name == identity,
+    }
+}
+
+/// Checks that a Rekor log of `trust_root` recorded the signature in the
+/// bundle, and returns the digest of the content it recorded, with its
+/// algorithm.
+fn check_log_entry(
+    entry: &Entry,
+    trust_root: &TrustRoot,
+) -> Result<(HashAlgorithm, String), String> {
+    let log_key = pem_blocks(&trust_root.rekor_keys)
+        .into_iter()
+        .map(|block| {
+            SubjectPublicKeyInfoOwned::from_pem(block)
+                .map_err(|e| format!("Invalid trustRoot rekorKeys, {}", e))
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .find(|key| log_id(key).is_some_and(|log_id| log_id == entry.log_id))
+        .ok_or_else(|| {
+            format!(
+                "The bundle is from log {}, which is not trusted",
+                entry.log_id
+            )
+        })?;
+
+    // The canonical JSON of the entry: its keys sorted, without whitespace.
+    let payload = format!(
+        r#"{{"body":{},"integratedTime":{},"logID":{},"logIndex":{}}}"#,
+        serde_json::Value::from(entry.body.as_str()),
+        entry.integrated_time,
+        serde_json::Value::from(entry.log_id.as_str()),
+        entry.log_index
+    );
+    let log_key = PublicKey::from_spki(&log_key)?;
+    let prehash = log_key.hash_algorithm().hash(payload.as_bytes());
+    if !log_key.verify(&prehash, &entry.signed_entry_timestamp) {
+        return Err("The signed entry timestamp does not match".to_string());
+    }
+
+    let body = base64(&entry.body)?;
+    let record: HashedRekord = serde_json::from_slice(&body)
+        .map_err(|e| format!("Invalid transparency log entry, {}", e))?;
+    if record.kind != "hashedrekord" {
+        return Err(format!(
+            "Unsupported transparency log entry {}",
+            record.kind
+        ));
+    }
+    let hash = record.spec.data.hash;
+    let algorithm = HashAlgorithm::from_name(&hash.algorithm)
+        .ok_or_else(|| format!("Unsupported digest {} in the log entry", hash.algorithm))?;
+    if base64(&record.spec.signature.content)? != entry.signature
+        || certificate_der(&record.spec.signature.public_key.content)? != entry.certificate
+    {
+        return Err("The transparency log entry is for another signature".to_string());
+    }
+    Ok((algorithm, hash.value))
+}
+
+/// The ID Rekor identifies the log with `key` by: SHA-256 of its DER, in hex.
+fn log_id(key: &SubjectPublicKeyInfoOwned) -> Option<String> {
+    let der = key.to_der().ok()?;
+    Some(hex(&HashAlgorithm::Sha256.hash(&der)))
+}
+
+const STANDARD: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+fn base64(text: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(text.trim())
+        .map_err(|e| format!("Invalid base64 in the bundle, {}", e))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The DER of a certificate given in PEM, itself in base64.
+fn certificate_der(text: &str) -> Result<Vec<u8>, String> {
+    let pem = base64(text)?;
+    Certificate::from_pem(&pem)
+        .and_then(|certificate| certificate.to_der())
+        .map_err(|e| format!("Invalid signing certificate, {}", e))
+}
+
+/// The PEM blocks of `text`, each from its `-----BEGIN` line to its
+/// `-----END` one.
+fn pem_blocks(text: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("-----BEGIN") {
+        let Some(end) = rest[start..].find("-----END") else {
+            break;
+        };
+        let end = start
+            + end
+            + rest[start + end..]
+                .find('\n')
+                .unwrap_or(rest.len() - start - end);
+        blocks.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::configuration::SignatureFormat;
+
+    static CONTENT: &[u8] = b"tool binary\n";
+    // A root and an intermediate in the shape of Fulcio's, both P-384.
+    static TRUST_ROOT: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBljCCARygAwIBAgIBATAKBggqhkjOPQQDAzAqMRUwEwYDVQQKDAxzaWdzdG9y\n\
+ZS5kZXYxETAPBgNVBAMMCHNpZ3N0b3JlMB4XDTIxMDEwMTAwMDAwMFoXDTMxMDEw\n\
+MTAwMDAwMFowKjEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MREwDwYDVQQDDAhzaWdz\n\
+dG9yZTB2MBAGByqGSM49AgEGBSuBBAAiA2IABBtNjfN3ygyULYK/r/7sO/ex7ybv\n\
+i0bSJ6TaLG4bn8qLEKcjVpsgsD6DeIxILA4iparNQcwGx/Qu437JY9peZ/kctTQJ\n\
+nx0beHgKrMtdXSlRDgvC9IRlp1ZSwJXARlqWwqMWMBQwEgYDVR0TAQH/BAgwBgEB\n\
+/wIBATAKBggqhkjOPQQDAwNoADBlAjEAhS1Hij5MkZz52nAuFh0nnMI1z9zLLUpE\n\
+Ln83KIJVn5Pe8hbj+D9eTZ1yOzhZWIuXAjB8hZE+LVT7A+KE0SfIAnwaqSRWKabf\n\
+j7xfnzfnQ6iN4tLABww/YpTVrvr67JOAPFI=\n\
+-----END CERTIFICATE-----\n\
+-----BEGIN CERTIFICATE-----\n\
+MIIBozCCASmgAwIBAgIBAjAKBggqhkjOPQQDAzAqMRUwEwYDVQQKDAxzaWdzdG9y\n\
+ZS5kZXYxETAPBgNVBAMMCHNpZ3N0b3JlMB4XDTIyMDEwMTAwMDAwMFoXDTMxMDEw\n\
+MTAwMDAwMFowNzEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MR4wHAYDVQQDDBVzaWdz\n\
+dG9yZS1pbnRlcm1lZGlhdGUwdjAQBgcqhkjOPQIBBgUrgQQAIgNiAATTzOeoG5aX\n\
+4cIrbnYD7KbPnWCvR/57pww8uMoW3gnW1C8nAVffc83b/MJwSo234TsnG/Jrp7Dp\n\
+qmciVM8i9LDgeqwayOX+3v7j1JZjP/bD8mi3LpfXZt960UDlEDN7L/WjFjAUMBIG\n\
+A1UdEwEB/wQIMAYBAf8CAQAwCgYIKoZIzj0EAwMDaAAwZQIxAK0ih5EyC9smc/li\n\
+elMeYBSu+/+zSjDNOICcWNaCOA0LKeq8QWTJVv+uH/Evckdl+gIwHxkqFe7Wa4Ls\n\
+YEhbcecuOGaqqh2D4Tap+5UJFWopABzocag/D7yS6xkRTXa84gvR\n\
+-----END CERTIFICATE-----
+";
+    // A P-256 certificate the intermediate issued for ten minutes to a
+    // release workflow of `owner/tool`.
+    static CERTIFICATE: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIICQzCCAcmgAwIBAgIBAzAKBggqhkjOPQQDAzA3MRUwEwYDVQQKDAxzaWdzdG9y\n\
+ZS5kZXYxHjAcBgNVBAMMFXNpZ3N0b3JlLWludGVybWVkaWF0ZTAeFw0yNTAxMDEw\n\
+MDAwMDBaFw0yNTAxMDEwMDEwMDBaMAAwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNC\n\
+AASp64x+OBw9ILVSaodXTqTt08wWnKUPJhnJo+gjHuMCBxQm7jzyhZ9ISqwZow8Q\n\
+oyJMCEeziIhvGvVzRhRZ0ghJo4H8MIH5MA4GA1UdDwEB/wQEAwIHgDATBgNVHSUE\n\
+DDAKBggrBgEFBQcDAzBaBgNVHREBAf8EUDBOhkxodHRwczovL2dpdGh1Yi5jb20v\n\
+b3duZXIvdG9vbC8uZ2l0aHViL3dvcmtmbG93cy9yZWxlYXNlLnltbEByZWZzL3Rh\n\
+Z3MvdjEuMC4wMDkGCisGAQQBg78wAQEEK2h0dHBzOi8vdG9rZW4uYWN0aW9ucy5n\n\
+aXRodWJ1c2VyY29udGVudC5jb20wOwYKKwYBBAGDvzABCAQtDCtodHRwczovL3Rv\n\
+a2VuLmFjdGlvbnMuZ2l0aHVidXNlcmNvbnRlbnQuY29tMAoGCCqGSM49BAMDA2gA\n\
+MGUCMFPccSG0KSF6HFvsnEQjwMeX0Is+54KZYLvyJ4i3R8H9CV6PRZKjrvEW+0IH\n\
+RQ2pjwIxAM8GYJltsOQLYOTf2/DnD4Ls8toFAhCGUFMmk9RmY/S8slL73vz/ZDCb\n\
+g8v3Trb74g==\n\
+-----END CERTIFICATE-----
+";
+    // The P-256 key of a stand-in for Rekor.
+    static REKOR_KEY: &str = "\
+-----BEGIN PUBLIC KEY-----\n\
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE6QwEJ6KlHBUeI06hMEMEQkcS1rWy\n\
+gx5lYHO/5zXmAyqT4XIqHBBKaPKZSKUshm0mnY5GbZpNt6rLLU3RmSfr2w==\n\
+-----END PUBLIC KEY-----
+";
+    // The intermediate, with the same name and key, but not a certificate
+    // authority.
+    static NOT_CA_INTERMEDIATE: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBizCCARGgAwIBAgIBBDAKBggqhkjOPQQDAzAqMRUwEwYDVQQKDAxzaWdzdG9y\n\
+ZS5kZXYxETAPBgNVBAMMCHNpZ3N0b3JlMB4XDTIyMDEwMTAwMDAwMFoXDTMxMDEw\n\
+MTAwMDAwMFowNzEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MR4wHAYDVQQDDBVzaWdz\n\
+dG9yZS1pbnRlcm1lZGlhdGUwdjAQBgcqhkjOPQIBBgUrgQQAIgNiAATTzOeoG5aX\n\
+4cIrbnYD7KbPnWCvR/57pww8uMoW3gnW1C8nAVffc83b/MJwSo234TsnG/Jrp7Dp\n\
+qmciVM8i9LDgeqwayOX+3v7j1JZjP/bD8mi3LpfXZt960UDlEDN7L/UwCgYIKoZI\n\
+zj0EAwMDaAAwZQIwU2UIxf3hdJFiIm8DUwBFB7WGVh0B6+5vvCPD2LpCI7FCtCPB\n\
+t7AchsKLu/lPeFFmAjEAhDSL19NZQJfz+9S/zbtrZnyRfs2jXEOT5Jsnq5uBBFXU\n\
+PLpk6VtDZpleksUWPcy3\n\
+-----END CERTIFICATE-----
+";
+    // The intermediate again, as a certificate authority whose key usage
+    // does not include signing certificates.
+    static NO_CERT_SIGN_INTERMEDIATE: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBszCCATmgAwIBAgIBBTAKBggqhkjOPQQDAzAqMRUwEwYDVQQKDAxzaWdzdG9y\n\
+ZS5kZXYxETAPBgNVBAMMCHNpZ3N0b3JlMB4XDTIyMDEwMTAwMDAwMFoXDTMxMDEw\n\
+MTAwMDAwMFowNzEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MR4wHAYDVQQDDBVzaWdz\n\
+dG9yZS1pbnRlcm1lZGlhdGUwdjAQBgcqhkjOPQIBBgUrgQQAIgNiAATTzOeoG5aX\n\
+4cIrbnYD7KbPnWCvR/57pww8uMoW3gnW1C8nAVffc83b/MJwSo234TsnG/Jrp7Dp\n\
+qmciVM8i9LDgeqwayOX+3v7j1JZjP/bD8mi3LpfXZt960UDlEDN7L/WjJjAkMBIG\n\
+A1UdEwEB/wQIMAYBAf8CAQAwDgYDVR0PAQH/BAQDAgeAMAoGCCqGSM49BAMDA2gA\n\
+MGUCMQCOJzsNb3unVrUyw4UJTF5a1G93A12vvW+PLW7JvO1BTcnvknhdEo0MHoo/\n\
+TN21VGsCMDXYYqdjYNsgpsm79ytiAHsV6DrQnvoFc9vIy09T6+kMF4kX4vrJRKiF\n\
+LunDVrWd6A==\n\
+-----END CERTIFICATE-----
+";
+    // The Fulcio intermediate and root, then the Rekor key, of the Sigstore
+    // public-good instance, from `trusted_root.json` of its TUF repository.
+    static PUBLIC_GOOD_CERTIFICATES: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIICGjCCAaGgAwIBAgIUALnViVfnU0brJasmRkHrn/UnfaQwCgYIKoZIzj0EAwMw\n\
+KjEVMBMGA1UEChMMc2lnc3RvcmUuZGV2MREwDwYDVQQDEwhzaWdzdG9yZTAeFw0y\n\
+MjA0MTMyMDA2MTVaFw0zMTEwMDUxMzU2NThaMDcxFTATBgNVBAoTDHNpZ3N0b3Jl\n\
+LmRldjEeMBwGA1UEAxMVc2lnc3RvcmUtaW50ZXJtZWRpYXRlMHYwEAYHKoZIzj0C\n\
+AQYFK4EEACIDYgAE8RVS/ysH+NOvuDZyPIZtilgUF9NlarYpAd9HP1vBBH1U5CV7\n\
+7LSS7s0ZiH4nE7Hv7ptS6LvvR/STk798LVgMzLlJ4HeIfF3tHSaexLcYpSASr1kS\n\
+0N/RgBJz/9jWCiXno3sweTAOBgNVHQ8BAf8EBAMCAQYwEwYDVR0lBAwwCgYIKwYB\n\
+BQUHAwMwEgYDVR0TAQH/BAgwBgEB/wIBADAdBgNVHQ4EFgQU39Ppz1YkEZb5qNjp\n\
+KFWixi4YZD8wHwYDVR0jBBgwFoAUWMAeX5FFpWapesyQoZMi0CrFxfowCgYIKoZI\n\
+zj0EAwMDZwAwZAIwPCsQK4DYiZYDPIaDi5HFKnfxXx6ASSVmERfsynYBiX2X6SJR\n\
+nZU84/9DZdnFvvxmAjBOt6QpBlc4J/0DxvkTCqpclvziL6BCCPnjdlIB3Pu3BxsP\n\
+mygUY7Ii2zbdCdliiow=\n\
+-----END CERTIFICATE-----\n\
+-----BEGIN CERTIFICATE-----\n\
+MIIB9zCCAXygAwIBAgIUALZNAPFdxHPwjeDloDwyYChAO/4wCgYIKoZIzj0EAwMw\n\
+KjEVMBMGA1UEChMMc2lnc3RvcmUuZGV2MREwDwYDVQQDEwhzaWdzdG9yZTAeFw0y\n\
+MTEwMDcxMzU2NTlaFw0zMTEwMDUxMzU2NThaMCoxFTATBgNVBAoTDHNpZ3N0b3Jl\n\
+LmRldjERMA8GA1UEAxMIc2lnc3RvcmUwdjAQBgcqhkjOPQIBBgUrgQQAIgNiAAT7\n\
+XeFT4rb3PQGwS4IajtLk3/OlnpgangaBclYpsYBr5i+4ynB07ceb3LP0OIOZdxex\n\
+X69c5iVuyJRQ+Hz05yi+UF3uBWAlHpiS5sh0+H2GHE7SXrk1EC5m1Tr19L9gg92j\n\
+YzBhMA4GA1UdDwEB/wQEAwIBBjAPBgNVHRMBAf8EBTADAQH/MB0GA1UdDgQWBBRY\n\
+wB5fkUWlZql6zJChkyLQKsXF+jAfBgNVHSMEGDAWgBRYwB5fkUWlZql6zJChkyLQ\n\
+KsXF+jAKBggqhkjOPQQDAwNpADBmAjEAj1nHeXZp+13NWBNa+EDsDP8G1WWg1tCM\n\
+WP/WHPqpaVo0jhsweNFZgSs0eE7wYI4qAjEA2WB9ot98sIkoF3vZYdd3/VtWB5b9\n\
+TNMea7Ix/stJ5TfcLLeABLE4BNJOsQ4vnBHJ\n\
+-----END CERTIFICATE-----
+";
+    static PUBLIC_GOOD_REKOR_KEY: &str = "\
+-----BEGIN PUBLIC KEY-----\n\
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE2G2Y+2tabdTV5BcGiBIx0a9fAFwr\n\
+kBbmLSGtks4L3qX6yYY0zufBnhC8Ur/iy55GhWP/9A/bY2LhC30M9+RYtw==\n\
+-----END PUBLIC KEY-----
+";
+    static SIGNATURE: &str = "MEQCIHraaiMsONSoBvQ0FNPOih6U4b2dOF7Q7iFCE4o/8IBfAiAXCBeMRP1Pz2vFyAUEg+Vef1Zxcl5z9gwvombyedqI5w==";
+    static SIGNED_ENTRY_TIMESTAMP: &str = "MEUCIBWtV9jUoFtoloIlwiIg3lhET6hSDwW07tYP2KLvx4umAiEA4h+/FPRBKxygeTeL/IO81+5o7M4RiNA0nb7kDT6YcpY=";
+    static LOG_ID: &str = "8da7c272129c2a144e6ed645b1c72d4c08d1e3e1d7da6f3c931879721eb01177";
+    const INTEGRATED_TIME: i64 = 1735689660;
+
+    fn configuration() -> Signature {
+        Signature {
+            format: SignatureFormat::Cosign,
+            url: None,
+            public_key: None,
+            keyring: None,
+            identity: Some(
+                "https://github.com/owner/tool/.github/workflows/release.yml@refs/tags/v1.0.0"
+                    .to_string(),
+            ),
+            issuer: Some("https://token.actions.githubusercontent.com".to_string()),
+            trust_root: Some(TrustRoot {
+                certificates: TRUST_ROOT.to_string(),
+                rekor_keys: REKOR_KEY.to_string(),
+            }),
+        }
+    }
+
+    /// The `hashedrekord` entry Rekor records for the signature, in base64.
+    fn body() -> String {
+        let record = serde_json::json!({
+            "apiVersion": "0.0.1",
+            "kind": "hashedrekord",
+            "spec": {
+                "data": {
+                    "hash": {"algorithm": "sha256", "value": hex(&sha2::Sha256::digest(CONTENT))},
+                },
+                "signature": {
+                    "content": SIGNATURE,
+                    "publicKey": {"content": STANDARD.encode(CERTIFICATE)},
+                },
+            },
+        });
+        STANDARD.encode(record.to_string())
+    }
+
+    /// A bundle as cosign wrote it before 2.4.
+    fn legacy_bundle(integrated_time: i64) -> Vec<u8> {
+        serde_json::json!({
+            "base64Signature": SIGNATURE,
+            "cert": STANDARD.encode(CERTIFICATE),
+            "rekorBundle": {
+                "SignedEntryTimestamp": SIGNED_ENTRY_TIMESTAMP,
+                "Payload": {
+                    "body": body(),
+                    "integratedTime": integrated_time,
+                    "logIndex": 12345,
+                    "logID": LOG_ID,
+                },
+            },
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// The same signature in a version 0.3 Sigstore bundle.
+    fn sigstore_bundle() -> Vec<u8> {
+        let certificate = Certificate::from_pem(CERTIFICATE)
+            .unwrap()
+            .to_der()
+            .unwrap();
+        let log_id: Vec<u8> = (0..LOG_ID.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&LOG_ID[index..index + 2], 16).unwrap())
+            .collect();
+        serde_json::json!({
+            "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+            "verificationMaterial": {
+                "certificate": {"rawBytes": STANDARD.encode(certificate)},
+                "tlogEntries": [{
+                    "logIndex": "12345",
+                    "logId": {"keyId": STANDARD.encode(log_id)},
+                    "kindVersion": {"kind": "hashedrekord", "version": "0.0.1"},
+                    "integratedTime": INTEGRATED_TIME.to_string(),
+                    "inclusionPromise": {"signedEntryTimestamp": SIGNED_ENTRY_TIMESTAMP},
+                    "canonicalizedBody": body(),
+                }],
+            },
+            "messageSignature": {
+                "messageDigest": {
+                    "algorithm": "SHA2_256",
+                    "digest": STANDARD.encode(sha2::Sha256::digest(CONTENT)),
+                },
+                "signature": SIGNATURE,
+            },
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_verify_bundles_in_either_format() {
+        // Arrange
+        let configuration = configuration();
+
+        // Act
+        let legacy = verify(&configuration, CONTENT, &legacy_bundle(INTEGRATED_TIME));
+        let sigstore = verify(&configuration, CONTENT, &sigstore_bundle());
+        let tampered = verify(&configuration, &b"tampered"[..], &sigstore_bundle());
+
+        // Assert
+        assert!(legacy.is_ok(), "{:?}", legacy);
+        assert!(sigstore.is_ok(), "{:?}", sigstore);
+        assert_eq!(
+            tampered.err().as_deref(),
+            Some("The transparency log entry is for another signature")
+        );
+    }
+
+    #[test]
+    fn test_identity_wildcard_only_as_last_path_segment() {
+        assert!(identity_matches(
+            "https://github.com/org/*",
+            "https://github.com/org/tool/.github/workflows/release.yml@refs/tags/v1.0.0"
+        ));
+        assert!(!identity_matches(
+            "https://github.com/org/*",
+            "https://github.com/org-evil/tool/.github/workflows/release.yml@refs/tags/v1.0.0"
+        ));
+        assert!(!identity_matches(
+            "https://github.com/org*",
+            "https://github.com/org-evil/tool/.github/workflows/release.yml@refs/tags/v1.0.0"
+        ));
+        assert!(check_identity_pattern("https://github.com/org/*").is_ok());
+        assert!(check_identity_pattern("https://github.com/org*").is_err());
+        assert!(check_identity_pattern("*@example.com").is_err());
+    }
+
+    #[test]
+    fn test_verify_checks_identity_and_issuer() {
+        // Arrange
+        let mut pattern = configuration();
+        pattern.identity = Some("https://github.com/owner/tool/*".to_string());
+        let mut other_identity = configuration();
+        other_identity.identity = Some("https://github.com/other/tool/*".to_string());
+        let mut inner_wildcard = configuration();
+        inner_wildcard.identity = Some(
+            "https://github.com/*/tool/.github/workflows/release.yml@refs/tags/v1.0.0".to_string(),
+        );
+        let mut other_issuer = configuration();
+        other_issuer.issuer = Some("https://accounts.google.com".to_string());
+
+        // Act
+        let matching = verify(&pattern, CONTENT, &sigstore_bundle());
+        let other_identity = verify(&other_identity, CONTENT, &sigstore_bundle());
+        let inner_wildcard = verify(&inner_wildcard, CONTENT, &sigstore_bundle());
+        let other_issuer = verify(&other_issuer, CONTENT, &sigstore_bundle());
+
+        // Assert
+        assert!(matching.is_ok(), "{:?}", matching);
+        let error = other_identity.unwrap_err();
+        assert!(
+            error.contains("not https://github.com/other/tool/*"),
+            "{}",
+            error
+        );
+        assert_eq!(
+            inner_wildcard.err().as_deref(),
+            Some(
+                "Identity https://github.com/*/tool/.github/workflows/release.yml@refs/tags/v1.0.0 \
+                 may only use * as its last path segment"
+            )
+        );
+        let error = other_issuer.unwrap_err();
+        assert!(
+            error.contains("not https://accounts.google.com"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_verify_requires_the_trust_root() {
+        // Arrange
+        let root_only = TRUST_ROOT
+            .split_inclusive("-----END CERTIFICATE-----\n")
+            .next();
+        let mut other_authority = configuration();
+        other_authority.trust_root.as_mut().unwrap().certificates = root_only.unwrap().to_string();
+        let mut other_log = configuration();
+        other_log.trust_root.as_mut().unwrap().rekor_keys = CERTIFICATE.to_string();
+        let mut untrusted = configuration();
+        untrusted.trust_root = None;
+
+        // Act
+        let other_authority = verify(&other_authority, CONTENT, &sigstore_bundle());
+        let other_log = verify(&other_log, CONTENT, &sigstore_bundle());
+        let untrusted = verify(&untrusted, CONTENT, &sigstore_bundle());
+
+        // Assert
+        assert_eq!(
+            other_authority.err().as_deref(),
+            Some(
+                "The signing certificate does not chain to the trustRoot, \
+unable to get local issuer certificate"
+            )
+        );
+        assert!(other_log.is_err());
+        assert!(untrusted.is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_signature_logged_after_certificate_expired() {
+        // Arrange
+        let configuration = configuration();
+
+        // Act
+        let result = verify(
+            &configuration,
+            CONTENT,
+            &legacy_bundle(INTEGRATED_TIME + 3600),
+        );
+
+        // Assert
+        assert_eq!(
+            result.err().as_deref(),
+            Some("The signing certificate had expired when the log recorded it")
+        );
+    }
+
+    #[test]
+    fn test_verify_requires_issuers_to_be_certificate_authorities() {
+        // Arrange
+        let root = TRUST_ROOT
+            .split_inclusive("-----END CERTIFICATE-----\n")
+            .next()
+            .unwrap();
+        let mut not_ca = configuration();
+        not_ca.trust_root.as_mut().unwrap().certificates =
+            format!("{}{}", root, NOT_CA_INTERMEDIATE);
+        let mut no_cert_sign = configuration();
+        no_cert_sign.trust_root.as_mut().unwrap().certificates =
+            format!("{}{}", root, NO_CERT_SIGN_INTERMEDIATE);
+        let mut intermediate_only = configuration();
+        intermediate_only.trust_root.as_mut().unwrap().certificates = TRUST_ROOT
+            .split_inclusive("-----END CERTIFICATE-----\n")
+            .nth(1)
+            .unwrap()
+            .to_string();
+
+        // Act
+        let not_ca = verify(&not_ca, CONTENT, &sigstore_bundle());
+        let no_cert_sign = verify(&no_cert_sign, CONTENT, &sigstore_bundle());
+        let intermediate_only = verify(&intermediate_only, CONTENT, &sigstore_bundle());
+
+        // Assert
+        assert_eq!(
+            not_ca.err().as_deref(),
+            Some("The signing certificate does not chain to the trustRoot, invalid CA certificate")
+        );
+        let error = no_cert_sign.unwrap_err();
+        assert!(
+            error.contains("does not chain to the trustRoot"),
+            "{}",
+            error
+        );
+        let error = intermediate_only.unwrap_err();
+        assert!(
+            error.contains("does not chain to the trustRoot"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_public_good_trust_root() {
+        // Arrange
+        let intermediate = Certificate::load_pem_chain(PUBLIC_GOOD_CERTIFICATES.as_bytes())
+            .unwrap()
+            .remove(0);
+        let der = intermediate.to_der().unwrap();
+        let trust_root = TrustRoot {
+            certificates: PUBLIC_GOOD_CERTIFICATES.to_string(),
+            rekor_keys: PUBLIC_GOOD_REKOR_KEY.to_string(),
+        };
+        let mut configuration = configuration();
+        configuration.trust_root = Some(trust_root.clone());
+        let rekor_key = SubjectPublicKeyInfoOwned::from_pem(PUBLIC_GOOD_REKOR_KEY).unwrap();
+
+        // Act
+        // 2024-01-01, then 2021-01-01, before the intermediate was issued.
+        let chained = check_issued(&intermediate, &der, &[], &trust_root, 1704067200);
+        let too_early = check_issued(&intermediate, &der, &[], &trust_root, 1609459200);
+        let foreign = verify(&configuration, CONTENT, &sigstore_bundle());
+
+        // Assert
+        assert!(chained.is_ok(), "{:?}", chained);
+        assert!(too_early.is_err());
+        let error = foreign.unwrap_err();
+        assert!(
+            error.contains("does not chain to the trustRoot"),
+            "{}",
+            error
+        );
+        // The log ID that bundles signed by rekor.sigstore.dev carry.
+        assert_eq!(
+            log_id(&rekor_key).as_deref(),
+            Some("c0d23d6ad406973f9559f3ba2d1ca01f84147d8ffc5b8445c224f98b9591801d")
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::credentials::{Credential, CredentialsFile};
+
+static CREDENTIALS_LOCATION: &str = "~/.config/launcher/credentials.yaml";
+static NETRC_LOCATION: &str = "~/.netrc";
+static VARIABLE_PREFIX: &str = "LAUNCHER_CREDENTIAL_";
+
+/// Where credentials are looked up: environment variables first, then the
+/// credentials file, then `~/.netrc`.
+pub struct CredentialSources {
+    pub variables: BTreeMap<String, String>,
+    pub credentials_path: PathBuf,
+    pub netrc_path: PathBuf,
+}
+
+impl CredentialSources {
+    pub fn from_environment() -> Self {
+        CredentialSources {
+            variables: std::env::vars()
+                .filter(|(name, _)| name.starts_with(VARIABLE_PREFIX))
+                .collect(),
+            credentials_path: PathBuf::from(shellexpand::tilde(CREDENTIALS_LOCATION).as_ref()),
+            netrc_path: PathBuf::from(shellexpand::tilde(NETRC_LOCATION).as_ref()),
+        }
+    }
+
+    /// Finds the credential to download `url` with.
+    ///
+    /// A credential named by the configuration is looked up by that name and
+    /// must exist. Otherwise the host of `url` is the key, and `~/.netrc` is
+    /// consulted as well; finding nothing means an anonymous download.
+    pub async fn resolve(
+        &self,
+        name: Option<&str>,
+        url: &str,
+    ) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL {}, {}", url, e))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| format!("No host in URL {}", url))?;
+        let key = name.unwrap_or(host);
+
+        let credential = match self.find_in_variables(key) {
+            Some(credential) => Some(credential),
+            None => self.find_in_credentials_file(key).await?,
+        };
+        if let Some(credential) = credential {
+            if let Some(allowed) = &credential.host
+                && allowed != host
+            {
+                return Err(format!(
+                    "Credential {} is restricted to {} and cannot be sent to {}",
+                    key, allowed, host
+                )
+                .into());
+            }
+            return Ok(Some(credential));
+        }
+        if let Some(name) = name {
+            return Err(format!(
+                "Credential {} not found, set {}{}_TOKEN or add it to {}",
+                name,
+                VARIABLE_PREFIX,
+                variable_key(name),
+                self.credentials_path.display()
+            )
+            .into());
+        }
+        self.find_in_netrc(host).await
+    }
+
+    /// `LAUNCHER_CREDENTIAL_<KEY>_TOKEN`, or `_USERNAME` and `_PASSWORD`,
+    /// with `KEY` upper-cased and anything not alphanumeric replaced by `_`.
+    /// `_HOST` restricts it to a host, as `host` does in the credentials file.
+    fn find_in_variables(&self, key: &str) -> Option<Credential> {
+        let variable = |suffix: &str| {
+            self.variables
+                .get(&format!(
+                    "{}{}_{}",
+                    VARIABLE_PREFIX,
+                    variable_key(key),
+                    suffix
+                ))
+                .cloned()
+        };
+        let credential = Credential {
+            host: variable("HOST"),
+            token: variable("TOKEN"),
+            username: variable("USERNAME"),
+            password: variable("PASSWORD"),
+            ..Credential::default()
+        };
+        (credential.token.is_some() || credential.username.is_some()).then_some(credential)
+    }
+
+    async fn find_in_credentials_file(
+        &self,
+        key: &str,
+    ) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
+        let Some(content) = read_if_exists(&self.credentials_path).await? else {
+            return Ok(None);
+        };
+        let mut file: CredentialsFile = serde_yaml::from_str(&content).map_err(|e| {
+            format!(
+                "Failed to parse credentials file {}, {}",
+                self.credentials_path.display(),
+                e
+            )
+        })?;
+        Ok(file.credentials.remove(key))
+    }
+
+    async fn find_in_netrc(
+        &self,
+        host: &str,
+    ) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
+        let Some(content) = read_if_exists(&self.netrc_path).await? else {
+            return Ok(None);
+        };
+        Ok(parse_netrc(&content, host))
+    }
+}
+
+fn variable_key(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+async fn read_if_exists(path: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read file {}, {}", path.display(), e).into()),
+    }
+}
+
+/// Finds the login for `host` in a netrc file, falling back to its `default`
+/// entry. Macro definitions are skipped.
+pub fn parse_netrc(content: &str, host: &str) -> Option<Credential> {
+    let mut tokens = Vec::new();
+    let mut in_macro = false;
+    for line in content.lines() {
+        if in_macro {
+            in_macro = !line.trim().is_empty();
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        while let Some(word) = words.next() {
+            if word == "macdef" {
+                words.next();
+                in_macro = true;
+                break;
+            }
+            tokens.push(word);
+        }
+    }
+
+    // Each entry is a machine name, or None for `default`, with its login.
+    let mut entries: Vec<(Option<&str>, Credential)> = Vec::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => entries.push((Some(tokens.next()?), Credential::default())),
+            "default" => entries.push((None, Credential::default())),
+            "login" | "password" | "account" => {
+                let value = tokens.next()?.to_string();
+                if let Some((_, credential)) = entries.last_mut() {
+                    match token {
+                        "login" => credential.username = Some(value),
+                        "password" => credential.password = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let position = entries
+        .iter()
+        .position(|(machine, _)| *machine == Some(host))
+        .or_else(|| entries.iter().position(|(machine, _)| machine.is_none()))?;
+    Some(entries.swap_remove(position).1).filter(|credential| credential.username.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sources(
+        dir: &Path,
+        variables: &[(&str, &str)],
+        credentials: Option<&str>,
+        netrc: Option<&str>,
+    ) -> CredentialSources {
+        let credentials_path = dir.join("credentials.yaml");
+        let netrc_path = dir.join("netrc");
+        if let Some(credentials) = credentials {
+            std::fs::write(&credentials_path, credentials).unwrap();
+        }
+        if let Some(netrc) = netrc {
+            std::fs::write(&netrc_path, netrc).unwrap();
+        }
+        CredentialSources {
+            variables: variables
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            credentials_path,
+            netrc_path,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_named_credential_from_variables() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(
+            dir.path(),
+            &[("LAUNCHER_CREDENTIAL_MY_ARTIFACTORY_TOKEN", "secret")],
+            Some("credentials:\n  my-artifactory:\n    token: from-file\n"),
+            None,
+        );
+
+        // Act
+        let credential = sources
+            .resolve(Some("my-artifactory"), "https://artifacts.example.com/tool")
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Assert
+        assert_eq!(credential.token.as_deref(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_by_host_from_credentials_file() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(
+            dir.path(),
+            &[],
+            Some(
+                r#"
+credentials:
+  artifacts.example.com:
+    username: me
+    password: hunter2
+    headers:
+      X-JFrog-Art-Api: key
+"#,
+            ),
+            None,
+        );
+
+        // Act
+        let credential = sources
+            .resolve(None, "https://artifacts.example.com/tool")
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Assert
+        assert_eq!(credential.username.as_deref(), Some("me"));
+        assert_eq!(credential.password.as_deref(), Some("hunter2"));
+        assert_eq!(credential.headers["X-JFrog-Art-Api"], "key");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_refuses_credential_restricted_to_another_host() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(
+            dir.path(),
+            &[],
+            Some("credentials:\n  internal:\n    host: artifacts.example.com\n    token: secret\n"),
+            None,
+        );
+
+        // Act
+        let result = sources
+            .resolve(Some("internal"), "https://attacker.example.org/tool")
+            .await;
+
+        // Assert
+        assert!(result.err().unwrap().to_string().contains("restricted"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_refuses_variables_restricted_to_another_host() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(
+            dir.path(),
+            &[
+                ("LAUNCHER_CREDENTIAL_INTERNAL_HOST", "artifacts.example.com"),
+                ("LAUNCHER_CREDENTIAL_INTERNAL_TOKEN", "secret"),
+            ],
+            None,
+            None,
+        );
+
+        // Act
+        let allowed = sources
+            .resolve(Some("internal"), "https://artifacts.example.com/tool")
+            .await;
+        let refused = sources
+            .resolve(Some("internal"), "https://attacker.example.org/tool")
+            .await;
+
+        // Assert
+        assert_eq!(
+            allowed.unwrap().unwrap().host.as_deref(),
+            Some("artifacts.example.com")
+        );
+        assert!(refused.err().unwrap().to_string().contains("restricted"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_missing_named_credential_fails() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(dir.path(), &[], None, None);
+
+        // Act
+        let result = sources
+            .resolve(Some("internal"), "https://artifacts.example.com/tool")
+            .await;
+
+        // Assert
+        assert!(
+            result
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("LAUNCHER_CREDENTIAL_INTERNAL_TOKEN")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_by_host_from_netrc() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let sources = sources(
+            dir.path(),
+            &[],
+            None,
+            Some("machine artifacts.example.com login me password hunter2\n"),
+        );
+
+        // Act
+        let credential = sources
+            .resolve(None, "https://artifacts.example.com/tool")
+            .await
+            .unwrap();
+        let anonymous = sources
+            .resolve(None, "https://example.org/tool")
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(credential.unwrap().username.as_deref(), Some("me"));
+        assert!(anonymous.is_none());
+    }
+
+    #[test]
+    fn test_parse_netrc_skips_macros_and_falls_back_to_default() {
+        // Arrange
+        let content = r#"
+machine other.example.com
+    login other
+    password other-secret
+macdef init
+    machine artifacts.example.com login macro password macro
+
+default login anonymous password guest
+"#;
+
+        // Act
+        let credential = parse_netrc(content, "artifacts.example.com").unwrap();
+
+        // Assert
+        assert_eq!(credential.username.as_deref(), Some("anonymous"));
+        assert_eq!(credential.password.as_deref(), Some("guest"));
+    }
+}
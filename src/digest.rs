@@ -0,0 +1,179 @@
+use std::fmt;
+
+use sha2::Digest as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Blake3 => "blake3",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Algorithm> {
+        [Algorithm::Sha256, Algorithm::Sha512, Algorithm::Blake3]
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    fn hex_length(self) -> usize {
+        match self {
+            Algorithm::Sha512 => 128,
+            Algorithm::Sha256 | Algorithm::Blake3 => 64,
+        }
+    }
+}
+
+/// An expected digest of a download, written `<algorithm>:<hex>`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Digest {
+    pub algorithm: Algorithm,
+    /// Lower-case hexadecimal.
+    pub hex: String,
+}
+
+impl Digest {
+    pub fn new(algorithm: Algorithm, hex: &str) -> Result<Digest, String> {
+        let hex = hex.trim();
+        if hex.len() != algorithm.hex_length() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "Invalid {} digest {}, expected {} hexadecimal characters",
+                algorithm.name(),
+                hex,
+                algorithm.hex_length()
+            ));
+        }
+        Ok(Digest {
+            algorithm,
+            hex: hex.to_ascii_lowercase(),
+        })
+    }
+
+    pub fn parse(value: &str) -> Result<Digest, String> {
+        let (algorithm, hex) = value
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid digest {}, expected <algorithm>:<hex>", value))?;
+        let algorithm = Algorithm::from_name(algorithm.trim())
+            .ok_or_else(|| format!("Unsupported digest algorithm {}", algorithm))?;
+        Digest::new(algorithm, hex)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), self.hex)
+    }
+}
+
+impl TryFrom<String> for Digest {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Digest::parse(&value)
+    }
+}
+
+impl From<Digest> for String {
+    fn from(digest: Digest) -> Self {
+        digest.to_string()
+    }
+}
+
+/// Computes a digest with any of the supported algorithms.
+pub enum Hasher {
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn new(algorithm: Algorithm) -> Hasher {
+        match algorithm {
+            Algorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            Algorithm::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    pub fn finalize(self) -> Digest {
+        let (algorithm, hex) = match self {
+            Hasher::Sha256(hasher) => (Algorithm::Sha256, format!("{:x}", hasher.finalize())),
+            Hasher::Sha512(hasher) => (Algorithm::Sha512, format!("{:x}", hasher.finalize())),
+            Hasher::Blake3(hasher) => (Algorithm::Blake3, hasher.finalize().to_hex().to_string()),
+        };
+        Digest { algorithm, hex }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest_of(algorithm: Algorithm, data: &[u8]) -> Digest {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_hasher_computes_each_algorithm() {
+        // Act
+        let sha256 = digest_of(Algorithm::Sha256, b"abc");
+        let sha512 = digest_of(Algorithm::Sha512, b"abc");
+        let blake3 = digest_of(Algorithm::Blake3, b"abc");
+
+        // Assert
+        assert_eq!(
+            sha256.hex,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(sha512.hex.starts_with("ddaf35a193617aba"));
+        assert_eq!(sha512.hex.len(), 128);
+        assert_eq!(
+            blake3.hex,
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trips() {
+        // Arrange
+        let value = format!("SHA512:{}", "AB".repeat(64));
+
+        // Act
+        let digest = Digest::parse(&value).unwrap();
+
+        // Assert
+        assert_eq!(digest.algorithm, Algorithm::Sha512);
+        assert_eq!(digest.to_string(), format!("sha512:{}", "ab".repeat(64)));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_digests() {
+        // Assert
+        assert!(Digest::parse(&"a".repeat(64)).is_err());
+        assert!(Digest::parse(&format!("md5:{}", "a".repeat(32))).is_err());
+        assert!(Digest::parse(&format!("sha512:{}", "a".repeat(64))).is_err());
+        assert!(Digest::parse(&format!("sha256:{}", "g".repeat(64))).is_err());
+    }
+}
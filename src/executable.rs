@@ -0,0 +1,293 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::models::configuration::File;
+use crate::providers::extractor::make_executable;
+use crate::template;
+
+/// Picks the entrypoint to run. The name of the shim the tool was invoked
+/// through (a symlink or a shebang shim) wins, then the first argument.
+/// Returns the entrypoint, if any, and how many leading arguments were
+/// consumed.
+pub fn select_entrypoint(
+    configuration: &File,
+    invoked_as: Option<&str>,
+    args: &[String],
+) -> (Option<String>, usize) {
+    if configuration.entrypoints.is_empty() {
+        return (None, 0);
+    }
+    if let Some(invoked_as) = invoked_as
+        && configuration.entrypoints.contains_key(invoked_as)
+    {
+        return (Some(invoked_as.to_string()), 0);
+    }
+    match args.first() {
+        Some(first) if configuration.entrypoints.contains_key(first) => (Some(first.clone()), 1),
+        _ => (None, 0),
+    }
+}
+
+/// Resolves the executable to run, relative to the extraction folder. A named
+/// entrypoint uses its own path; otherwise a per-platform `executable` wins
+/// over the top-level one, which in turn defaults to the configuration's `name`.
+pub fn resolve_executable(
+    configuration: &File,
+    entrypoint: Option<&str>,
+    os: &str,
+    arch: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let pattern = match entrypoint {
+        Some(entrypoint) => configuration
+            .entrypoints
+            .get(entrypoint)
+            .ok_or_else(|| format!("Entrypoint {} not found", entrypoint))?,
+        None => configuration
+            .platforms
+            .get(os)
+            .and_then(|platform| platform.get(arch))
+            .and_then(|architecture| architecture.executable.as_ref())
+            .or(configuration.executable.as_ref())
+            .unwrap_or(&configuration.name),
+    };
+    let executable = template::render(pattern, &configuration.version, os, arch);
+
+    let path = Path::new(&executable);
+    if executable.is_empty()
+        || !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!(
+            "Executable {} must be a relative path inside the archive",
+            executable
+        )
+        .into());
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Makes the default executable and every entrypoint in `folder` executable,
+/// whichever one this launch runs, as zip archives and plain binaries carry no
+/// mode bits and the install is not repeated for the others. A default
+/// executable the artifact does not contain is skipped.
+pub async fn make_executables(
+    configuration: &File,
+    folder: &Path,
+    os: &str,
+    arch: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let default = folder.join(resolve_executable(configuration, None, os, arch)?);
+    if tokio::fs::metadata(&default).await.is_ok() {
+        make_executable(&default).await?;
+    }
+    for name in configuration.entrypoints.keys() {
+        make_executable(&folder.join(resolve_executable(configuration, Some(name), os, arch)?))
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::extractor::Extractor;
+    use crate::providers::extractor::test_support::write_archive;
+    use crate::providers::zip_extractor::ZipExtractor;
+    use std::io::Write;
+
+    fn configuration(yaml: &str) -> File {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_executable_defaults_to_name() {
+        let configuration = configuration(
+            r#"
+name: terraform
+version: 1.11.3
+urlPattern: https://example.com/terraform.zip
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+"#,
+        );
+
+        let executable = resolve_executable(&configuration, None, "linux", "amd64").unwrap();
+
+        assert_eq!(executable, PathBuf::from("terraform"));
+    }
+
+    #[test]
+    fn test_resolve_executable_renders_template() {
+        let configuration = configuration(
+            r#"
+name: helm
+version: 3.17.0
+urlPattern: https://example.com/helm.tar.gz
+executable: "{{os}}-{{arch}}/helm"
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+"#,
+        );
+
+        let executable = resolve_executable(&configuration, None, "linux", "amd64").unwrap();
+
+        assert_eq!(executable, PathBuf::from("linux-amd64/helm"));
+    }
+
+    #[test]
+    fn test_resolve_executable_prefers_platform_override() {
+        let configuration = configuration(
+            r#"
+name: tool
+version: 1.0.0
+urlPattern: https://example.com/tool.zip
+bin: bin/tool
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+    windows:
+        amd64:
+            sha256: def
+            executable: bin/tool.exe
+"#,
+        );
+
+        assert_eq!(
+            resolve_executable(&configuration, None, "linux", "amd64").unwrap(),
+            PathBuf::from("bin/tool")
+        );
+        assert_eq!(
+            resolve_executable(&configuration, None, "windows", "amd64").unwrap(),
+            PathBuf::from("bin/tool.exe")
+        );
+    }
+
+    #[test]
+    fn test_resolve_executable_rejects_paths_outside_archive() {
+        let configuration = configuration(
+            r#"
+name: tool
+version: 1.0.0
+urlPattern: https://example.com/tool.zip
+executable: ../../usr/bin/tool
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+"#,
+        );
+
+        assert!(resolve_executable(&configuration, None, "linux", "amd64").is_err());
+    }
+
+    fn multi_binary_configuration() -> File {
+        configuration(
+            r#"
+name: go
+version: 1.24.0
+urlPattern: https://example.com/go{{version}}.{{os}}-{{arch}}.tar.gz
+stripPrefix: go
+executable: bin/go
+entrypoints:
+    go: bin/go
+    gofmt: bin/gofmt
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+"#,
+        )
+    }
+
+    #[test]
+    fn test_select_entrypoint_from_invoked_name() {
+        let configuration = multi_binary_configuration();
+        let args = vec!["-l".to_string(), ".".to_string()];
+
+        let selected = select_entrypoint(&configuration, Some("gofmt"), &args);
+
+        assert_eq!(selected, (Some("gofmt".to_string()), 0));
+    }
+
+    #[test]
+    fn test_select_entrypoint_from_first_argument() {
+        let configuration = multi_binary_configuration();
+        let args = vec!["gofmt".to_string(), "-l".to_string()];
+
+        let selected = select_entrypoint(&configuration, Some("toolchain"), &args);
+
+        assert_eq!(selected, (Some("gofmt".to_string()), 1));
+    }
+
+    #[test]
+    fn test_select_entrypoint_from_first_argument_when_run_directly() {
+        let configuration = multi_binary_configuration();
+        let args = vec!["gofmt".to_string(), "-l".to_string()];
+
+        let selected = select_entrypoint(&configuration, None, &args);
+
+        assert_eq!(selected, (Some("gofmt".to_string()), 1));
+    }
+
+    #[test]
+    fn test_select_entrypoint_falls_back_to_default_executable() {
+        let configuration = multi_binary_configuration();
+        let args = vec!["build".to_string()];
+
+        let selected = select_entrypoint(&configuration, Some("toolchain"), &args);
+
+        assert_eq!(selected, (None, 0));
+    }
+
+    #[tokio::test]
+    async fn test_make_executables_after_install_through_entrypoint() {
+        // Arrange
+        let configuration = multi_binary_configuration();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let folder = temp_dir.path().join("downloaded");
+        let mut content = Vec::new();
+        {
+            let mut zip_writer = zip::ZipWriter::new(std::io::Cursor::new(&mut content));
+            let options: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+            for (name, output) in [("go/bin/go", "go"), ("go/bin/gofmt", "gofmt")] {
+                zip_writer.start_file(name, options).unwrap();
+                write!(zip_writer, "#!/bin/sh\necho {}\n", output).unwrap();
+            }
+            zip_writer.finish().unwrap();
+        }
+        let archive_path = write_archive(temp_dir.path(), &content);
+        let entrypoint = folder
+            .join(resolve_executable(&configuration, Some("gofmt"), "linux", "amd64").unwrap());
+        ZipExtractor::new()
+            .extract_to_disk(&archive_path, &folder, &entrypoint, Some("go"))
+            .await
+            .unwrap();
+
+        // Act
+        make_executables(&configuration, &folder, "linux", "amd64")
+            .await
+            .unwrap();
+
+        // Assert
+        let default =
+            folder.join(resolve_executable(&configuration, None, "linux", "amd64").unwrap());
+        let output = std::process::Command::new(&default).output().unwrap();
+        assert_eq!(output.stdout, b"go\n");
+    }
+
+    #[test]
+    fn test_resolve_executable_for_entrypoint() {
+        let configuration = multi_binary_configuration();
+
+        assert_eq!(
+            resolve_executable(&configuration, Some("gofmt"), "linux", "amd64").unwrap(),
+            PathBuf::from("bin/gofmt")
+        );
+        assert!(resolve_executable(&configuration, Some("vet"), "linux", "amd64").is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::credentials::CredentialSources;
+use crate::models::configuration::File;
+use crate::models::credentials::Credential;
+use crate::models::github::{Asset, Release};
+use crate::template;
+
+pub static DEFAULT_API_URL: &str = "https://api.github.com";
+static DEFAULT_API_HOST: &str = "api.github.com";
+
+pub fn api_url(configuration: &File) -> &str {
+    configuration
+        .api_url
+        .as_deref()
+        .unwrap_or(DEFAULT_API_URL)
+        .trim_end_matches('/')
+}
+
+pub fn repo(configuration: &File) -> Result<&str, Box<dyn std::error::Error>> {
+    let repo = configuration
+        .repo
+        .as_deref()
+        .ok_or("The github source requires repo")?;
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok(repo)
+        }
+        _ => Err(format!("Invalid repo {}, expected owner/name", repo).into()),
+    }
+}
+
+/// Tags to look the release up by, in order.
+pub fn tag_candidates(configuration: &File, os: &str, arch: &str) -> Vec<String> {
+    match &configuration.tag {
+        Some(tag) => vec![template::render(tag, &configuration.version, os, arch)],
+        None => vec![
+            format!("v{}", configuration.version),
+            configuration.version.clone(),
+        ],
+    }
+}
+
+/// Identifies what an asset URL was resolved from, so that a cached
+/// resolution is only reused for the same release and asset.
+pub fn asset_key(configuration: &File, os: &str, arch: &str) -> String {
+    format!(
+        "{}/repos/{}/releases/tags/{} {}",
+        api_url(configuration),
+        configuration.repo.as_deref().unwrap_or_default(),
+        tag_candidates(configuration, os, arch).join(","),
+        template::render(
+            configuration.asset.as_deref().unwrap_or_default(),
+            &configuration.version,
+            os,
+            arch
+        )
+    )
+}
+
+/// The credential to query the API of `configuration` with.
+///
+/// For `api.github.com` this is the credential the configuration names or one
+/// found for the host, else a token from the environment. Any other API only
+/// gets a credential the configuration names and that is restricted to its
+/// host, so that `GITHUB_TOKEN` never reaches a server it was not issued by.
+pub async fn credential(
+    configuration: &File,
+) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
+    resolve_credential(
+        configuration,
+        &CredentialSources::from_environment(),
+        token_from_environment(),
+    )
+    .await
+}
+
+async fn resolve_credential(
+    configuration: &File,
+    sources: &CredentialSources,
+    environment_token: Option<Credential>,
+) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
+    let url = api_url(configuration);
+    let host = reqwest::Url::parse(url)
+        .map_err(|e| format!("Invalid apiUrl {}, {}", url, e))?
+        .host_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("No host in apiUrl {}", url))?;
+    if host == DEFAULT_API_HOST {
+        let credential = sources
+            .resolve(configuration.credential.as_deref(), url)
+            .await?;
+        return Ok(credential.or(environment_token));
+    }
+
+    let Some(name) = configuration.credential.as_deref() else {
+        return Ok(None);
+    };
+    let credential = sources.resolve(Some(name), url).await?;
+    match credential {
+        Some(credential) if credential.host.as_deref() == Some(host.as_str()) => {
+            Ok(Some(credential))
+        }
+        _ => Err(format!(
+            "Credential {} must be restricted to host {} to be sent to its API",
+            name, host
+        )
+        .into()),
+    }
+}
+
+/// The token in `GITHUB_TOKEN` or `GH_TOKEN`, as the GitHub CLI uses them.
+fn token_from_environment() -> Option<Credential> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|token| !token.is_empty()))
+        .map(|token| Credential {
+            token: Some(token),
+            ..Credential::default()
+        })
+}
+
+/// Picks the single asset of `release` whose name matches `pattern`.
+pub fn select_asset<'a>(
+    release: &'a Release,
+    pattern: &str,
+) -> Result<&'a Asset, Box<dyn std::error::Error>> {
+    let matching: Vec<&Asset> = release
+        .assets
+        .iter()
+        .filter(|asset| glob_match(pattern, &asset.name))
+        .collect();
+    match matching.as_slice() {
+        [asset] => Ok(asset),
+        [] => Err(format!(
+            "No asset of release {} matches {}, available: {}",
+            release.tag_name,
+            pattern,
+            asset_names(release.assets.iter())
+        )
+        .into()),
+        _ => Err(format!(
+            "Several assets of release {} match {}: {}",
+            release.tag_name,
+            pattern,
+            asset_names(matching.into_iter())
+        )
+        .into()),
+    }
+}
+
+fn asset_names<'a>(assets: impl Iterator<Item = &'a Asset>) -> String {
+    assets
+        .map(|asset| asset.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Matches `name` against `pattern`, where `*` stands for any run of
+/// characters.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(names: &[&str]) -> Release {
+        Release {
+            tag_name: "v1.0.0".to_string(),
+            draft: false,
+            assets: names
+                .iter()
+                .map(|name| Asset {
+                    name: name.to_string(),
+                    url: format!("https://api.github.com/assets/{}", name),
+                    browser_download_url: format!("https://github.com/download/{}", name),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        // Assert
+        assert!(glob_match("tool_linux_amd64.zip", "tool_linux_amd64.zip"));
+        assert!(glob_match(
+            "tool_*_linux_amd64.zip",
+            "tool_1.0.0_linux_amd64.zip"
+        ));
+        assert!(glob_match("*linux*amd64*", "tool-linux-x-amd64.tar.gz"));
+        assert!(!glob_match(
+            "tool_*_linux_amd64.zip",
+            "tool_1.0.0_linux_arm64.zip"
+        ));
+        assert!(!glob_match("tool*.zip", "tool.zip.sha256"));
+        assert!(!glob_match("a*aa", "aa"));
+    }
+
+    #[test]
+    fn test_select_asset_requires_a_single_match() {
+        // Arrange
+        let release = release(&["tool_linux_amd64.zip", "tool_linux_arm64.zip", "SHA256SUMS"]);
+
+        // Act
+        let asset = select_asset(&release, "tool_linux_*64.zip");
+
+        // Assert
+        assert!(asset.is_err(), "both assets match");
+        assert_eq!(
+            select_asset(&release, "tool_linux_amd64*").unwrap().name,
+            "tool_linux_amd64.zip"
+        );
+    }
+
+    #[test]
+    fn test_select_asset_without_match_lists_assets() {
+        // Arrange
+        let release = release(&["tool_darwin_arm64.zip"]);
+
+        // Act
+        let result = select_asset(&release, "tool_linux_amd64.zip");
+
+        // Assert
+        assert!(
+            result
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("tool_darwin_arm64.zip")
+        );
+    }
+
+    #[test]
+    fn test_repo_requires_owner_and_name() {
+        // Arrange
+        let mut configuration: File =
+            serde_yaml::from_str("name: t\nversion: 1.0.0\nsource: github\nplatforms: {}\n")
+                .unwrap();
+
+        // Assert
+        assert!(repo(&configuration).is_err());
+        configuration.repo = Some("hashicorp".to_string());
+        assert!(repo(&configuration).is_err());
+        configuration.repo = Some("hashicorp/terraform".to_string());
+        assert_eq!(repo(&configuration).unwrap(), "hashicorp/terraform");
+    }
+
+    fn sources(dir: &std::path::Path, credentials: &str) -> CredentialSources {
+        let credentials_path = dir.join("credentials.yaml");
+        std::fs::write(&credentials_path, credentials).unwrap();
+        CredentialSources {
+            variables: Default::default(),
+            credentials_path,
+            netrc_path: dir.join("netrc"),
+        }
+    }
+
+    fn token(token: &str) -> Option<Credential> {
+        Some(Credential {
+            token: Some(token.to_string()),
+            ..Credential::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_credential_keeps_environment_token_to_api_github_com() {
+        // Arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sources = sources(
+            temp_dir.path(),
+            "credentials:\n  ghe:\n    host: github.example.com\n    token: ghe-token\n",
+        );
+        let mut configuration: File =
+            serde_yaml::from_str("name: t\nversion: 1.0.0\nsource: github\nplatforms: {}\n")
+                .unwrap();
+
+        // Act
+        let github = resolve_credential(&configuration, &sources, token("github-token")).await;
+        configuration.api_url = Some("https://github.example.com/api/v3".to_string());
+        let unnamed = resolve_credential(&configuration, &sources, token("github-token")).await;
+        configuration.credential = Some("ghe".to_string());
+        let named = resolve_credential(&configuration, &sources, token("github-token")).await;
+        configuration.api_url = Some("https://attacker.example.com".to_string());
+        let other_host = resolve_credential(&configuration, &sources, token("github-token")).await;
+
+        // Assert
+        assert_eq!(github.unwrap(), token("github-token"));
+        assert_eq!(unnamed.unwrap(), None);
+        assert_eq!(named.unwrap().unwrap().token.as_deref(), Some("ghe-token"));
+        assert!(other_host.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_credential_requires_host_binding_for_other_apis() {
+        // Arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sources = sources(
+            temp_dir.path(),
+            "credentials:\n  ghe:\n    token: unbound-token\n",
+        );
+        let mut configuration: File =
+            serde_yaml::from_str("name: t\nversion: 1.0.0\nsource: github\nplatforms: {}\n")
+                .unwrap();
+        configuration.api_url = Some("https://github.example.com/api/v3".to_string());
+        configuration.credential = Some("ghe".to_string());
+
+        // Act
+        let result = resolve_credential(&configuration, &sources, None).await;
+
+        // Assert
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Credential ghe must be restricted to host github.example.com to be sent to its API"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::path::{Path, PathBuf};
+
+use crate::models::configuration::File;
+
+static PROGRAM_NAME: &str = "launcher";
+
+pub enum Command {
+    Launch(Invocation),
+    /// `launcher shim ...`, with the arguments that follow `shim`.
+    Shim(Vec<String>),
+    /// `launcher init ...`, with the arguments that follow `init`.
+    Init(Vec<String>),
+    /// `launcher bump ...`, with the arguments that follow `bump`.
+    Bump(Vec<String>),
+}
+
+pub struct Invocation {
+    pub config_path: PathBuf,
+    /// The name the tool was invoked through, used to pick an entrypoint.
+    pub invoked_as: Option<String>,
+    pub args: Vec<String>,
+}
+
+/// Works out what to do from the process arguments.
+///
+/// Invoked as `launcher`, the first argument is either a subcommand or the
+/// configuration path, which is what a `#!` line produces. A configuration
+/// without a `.yaml` or `.yml` extension is a shebang shim, so its name picks
+/// the entrypoint; a configuration file run directly leaves that to the first
+/// argument. Invoked through a symlink under any other name, the configuration
+/// is looked up by that name in `tools_dir` and every argument is passed
+/// through.
+pub async fn parse_command(
+    argv: Vec<String>,
+    tools_dir: &Path,
+) -> Result<Command, Box<dyn std::error::Error>> {
+    let mut argv = argv.into_iter();
+    let program = argv.next().unwrap_or_else(|| PROGRAM_NAME.to_string());
+    let program_name = Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(PROGRAM_NAME)
+        .to_string();
+
+    if program_name == PROGRAM_NAME {
+        let first = argv.next().ok_or_else(|| {
+            format!(
+                "Usage: {} <path to configuration file> [args...]\n       {} shim <install|list|remove> ...\n       {} init --name <name> --version <version> --url-pattern <pattern> --platform <os/arch>...\n       {} bump [--dry-run] <config> <version>",
+                program, program, program, program
+            )
+        })?;
+        if first == "shim" {
+            return Ok(Command::Shim(argv.collect()));
+        }
+        if first == "init" {
+            return Ok(Command::Init(argv.collect()));
+        }
+        if first == "bump" {
+            return Ok(Command::Bump(argv.collect()));
+        }
+        let config_path = PathBuf::from(first);
+        let is_yaml = matches!(
+            config_path
+                .extension()
+                .and_then(|extension| extension.to_str()),
+            Some("yaml" | "yml")
+        );
+        let invoked_as = config_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|_| !is_yaml)
+            .map(str::to_string);
+        return Ok(Command::Launch(Invocation {
+            config_path,
+            invoked_as,
+            args: argv.collect(),
+        }));
+    }
+
+    let config_path = find_tool_configuration(tools_dir, &program_name).await?;
+    Ok(Command::Launch(Invocation {
+        config_path,
+        invoked_as: Some(program_name),
+        args: argv.collect(),
+    }))
+}
+
+/// Looks for `<name>.yaml` (or `.yml`) in `tools_dir`, then for a
+/// configuration there that declares `name` as one of its entrypoints.
+pub async fn find_tool_configuration(
+    tools_dir: &Path,
+    name: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    for extension in ["yaml", "yml"] {
+        let candidate = tools_dir.join(format!("{}.{}", name, extension));
+        if tokio::fs::metadata(&candidate).await.is_ok() {
+            return Ok(candidate);
+        }
+    }
+
+    if let Ok(mut entries) = tokio::fs::read_dir(tools_dir).await {
+        let mut candidates = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            candidates.push(entry.path());
+        }
+        candidates.sort();
+        for candidate in candidates {
+            let is_yaml = matches!(
+                candidate
+                    .extension()
+                    .and_then(|extension| extension.to_str()),
+                Some("yaml" | "yml")
+            );
+            if !is_yaml {
+                continue;
+            }
+            let Ok(content) = tokio::fs::read_to_string(&candidate).await else {
+                continue;
+            };
+            if let Ok(configuration) = serde_yaml::from_str::<File>(&content)
+                && configuration.entrypoints.contains_key(name)
+            {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(format!(
+        "No configuration found for {} in {}",
+        name,
+        tools_dir.display()
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    async fn parse_invocation(
+        argv: Vec<String>,
+        tools_dir: &Path,
+    ) -> Result<Invocation, Box<dyn std::error::Error>> {
+        match parse_command(argv, tools_dir).await? {
+            Command::Launch(invocation) => Ok(invocation),
+            _ => Err("Expected a launch".into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_as_launcher_takes_config_path() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let invocation = parse_invocation(
+            argv(&[
+                "/usr/local/bin/launcher",
+                "./terraform.yaml",
+                "plan",
+                "-out=x",
+            ]),
+            tools_dir.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(invocation.config_path, PathBuf::from("./terraform.yaml"));
+        assert_eq!(invocation.invoked_as, None);
+        assert_eq!(invocation.args, argv(&["plan", "-out=x"]));
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_of_shebang_shim_uses_its_name() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let invocation = parse_invocation(
+            argv(&["/usr/local/bin/launcher", "/home/me/bin/gofmt", "-l"]),
+            tools_dir.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(invocation.config_path, PathBuf::from("/home/me/bin/gofmt"));
+        assert_eq!(invocation.invoked_as.as_deref(), Some("gofmt"));
+        assert_eq!(invocation.args, argv(&["-l"]));
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_as_launcher_without_config_fails() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let result = parse_invocation(argv(&["launcher"]), tools_dir.path()).await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_through_symlink_uses_tools_dir() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+        let config_path = tools_dir.path().join("terraform.yaml");
+        tokio::fs::write(&config_path, "name: terraform")
+            .await
+            .unwrap();
+
+        // Act
+        let invocation = parse_invocation(
+            argv(&["/home/me/bin/terraform", "launcher.yaml", "plan"]),
+            tools_dir.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(invocation.config_path, config_path);
+        assert_eq!(invocation.invoked_as.as_deref(), Some("terraform"));
+        assert_eq!(invocation.args, argv(&["launcher.yaml", "plan"]));
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_through_symlink_finds_entrypoint() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+        let config_path = tools_dir.path().join("go.yaml");
+        tokio::fs::write(
+            &config_path,
+            r#"
+name: go
+version: 1.24.0
+urlPattern: https://example.com/go{{version}}.{{os}}-{{arch}}.tar.gz
+entrypoints:
+    go: bin/go
+    gofmt: bin/gofmt
+platforms:
+    linux:
+        amd64:
+            sha256: abc
+"#,
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let invocation = parse_invocation(argv(&["gofmt", "-l", "."]), tools_dir.path())
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(invocation.config_path, config_path);
+        assert_eq!(invocation.invoked_as.as_deref(), Some("gofmt"));
+        assert_eq!(invocation.args, argv(&["-l", "."]));
+    }
+
+    #[tokio::test]
+    async fn test_parse_invocation_through_symlink_without_config_fails() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let result = parse_invocation(argv(&["kubectl", "get", "pods"]), tools_dir.path()).await;
+
+        // Assert
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("kubectl"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_command_shim() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let command = parse_command(
+            argv(&["launcher", "shim", "install", "terraform.yaml"]),
+            tools_dir.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        match command {
+            Command::Shim(args) => assert_eq!(args, argv(&["install", "terraform.yaml"])),
+            _ => panic!("Expected the shim subcommand"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_command_init() {
+        // Arrange
+        let tools_dir = tempdir().unwrap();
+
+        // Act
+        let command = parse_command(
+            argv(&["launcher", "init", "--name", "terraform"]),
+            tools_dir.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        match command {
+            Command::Init(args) => assert_eq!(args, argv(&["--name", "terraform"])),
+            _ => panic!("Expected the init subcommand"),
+        }
+    }
+}
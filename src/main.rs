@@ -1,20 +1,45 @@
-use bytes::Bytes;
 use models::download_marker::MarkerFile;
-use std::path::{self, PathBuf};
-use tokio::process::Command;
+use std::path::{self, Path};
+use std::os::unix::process::CommandExt;
 
 pub mod providers;
 use providers::{
-    downloader::Downloader, extractor::Extractor, file_marker_manager::FileMarkerManager,
-    marker_manager::MarkerManager, zip_extractor::ZipExtractor,
+    authenticated_downloader::AuthenticatedDownloader,
+    binary_extractor::BinaryExtractor,
+    downloader::Downloader,
+    extractor::Extractor,
+    file_marker_manager::FileMarkerManager,
+    github_downloader::GithubDownloader,
+    gzip_extractor::GzipExtractor,
+    marker_manager::MarkerManager,
+    tar_bz2_extractor::TarBz2Extractor,
+    tar_gz_extractor::TarGzExtractor,
+    tar_xz_extractor::TarXzExtractor,
+    tar_zst_extractor::TarZstExtractor,
+    zip_extractor::ZipExtractor,
 };
 
+pub mod artifact;
+pub mod checksums;
+pub mod commands;
+pub mod cosign;
+pub mod credentials;
+pub mod digest;
+pub mod executable;
+pub mod github;
+pub mod invocation;
 pub mod models;
-use crate::models::configuration::{Compression, CompressionType, File};
+pub mod openpgp;
+pub mod progress;
+pub mod retry;
+pub mod signature;
+pub mod template;
+pub mod version;
+use crate::models::configuration::{Compression, CompressionType, File, Source};
 
 use crate::providers::unauthenticated_downloader::UnauthenticatedDownloader;
 
-async fn read_configuration(path: &str) -> Result<File, Box<dyn std::error::Error>> {
+async fn read_configuration(path: &Path) -> Result<File, Box<dyn std::error::Error>> {
     let file = tokio::fs::read(path).await?;
     let content = String::from_utf8(file)?;
     let base_file: File = serde_yaml::from_str(&content)?;
@@ -22,12 +47,14 @@ async fn read_configuration(path: &str) -> Result<File, Box<dyn std::error::Erro
 }
 
 static CACHE_LOCATION: &str = "~/.launcher";
+static TOOLS_LOCATION: &str = "~/.config/launcher/tools";
 
 async fn extract_to_disk(
     compression: &Compression,
-    content: &Bytes,
-    folder: &PathBuf,
-    executable_path: &PathBuf,
+    archive_path: &Path,
+    folder: &Path,
+    executable_path: &Path,
+    strip_prefix: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if tokio::fs::metadata(folder).await.is_ok() {
         tokio::fs::remove_dir_all(folder.to_str().unwrap())
@@ -39,10 +66,46 @@ async fn extract_to_disk(
             })?;
     }
     match compression.compression_type {
+        CompressionType::None => {
+            let extractor = BinaryExtractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
+        CompressionType::Gzip => {
+            let extractor = GzipExtractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
         CompressionType::Zip => {
             let extractor = ZipExtractor::new();
             extractor
-                .extract_to_disk(content, folder, executable_path)
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
+        CompressionType::TarGz => {
+            let extractor = TarGzExtractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
+        CompressionType::TarXz => {
+            let extractor = TarXzExtractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
+        CompressionType::TarBz2 => {
+            let extractor = TarBz2Extractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
+                .await?;
+        }
+        CompressionType::TarZst => {
+            let extractor = TarZstExtractor::new();
+            extractor
+                .extract_to_disk(archive_path, folder, executable_path, strip_prefix)
                 .await?;
         }
     }
@@ -50,28 +113,95 @@ async fn extract_to_disk(
     Ok(())
 }
 
+/// Downloads and extracts the artifact with `downloader`, unless the one in
+/// the cache is already up to date.
+async fn install<D: Downloader>(
+    downloader: &D,
+    configuration: &File,
+    os: &str,
+    arch: &str,
+    command_cache_base: &Path,
+    executable_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let marker_manager = FileMarkerManager::new();
+    let download_path = command_cache_base.join("downloaded");
+    let digest_marker_path = command_cache_base.join("digest");
+    let archive_path = command_cache_base.join("download");
+
+    let (url, digest) = artifact::resolve(
+        downloader,
+        configuration,
+        os,
+        arch,
+        &command_cache_base.join("artifact"),
+    )
+    .await?;
+    let strip_prefix = configuration
+        .strip_prefix
+        .as_ref()
+        .map(|prefix| template::render(prefix, &configuration.version, os, arch));
+
+    marker_manager.invoke_if_different(
+        digest_marker_path,
+        &MarkerFile {
+            digest: digest.clone(),
+            url: url.clone(),
+        },
+        || async {
+            eprintln!("Downloading {}...", configuration.name);
+
+            downloader
+                .download_and_validate_digest(configuration, &url, &digest, &archive_path)
+                .await?;
+            downloader
+                .verify_signature(configuration, os, arch, &url, &archive_path)
+                .await?;
+
+            extract_to_disk(
+                &configuration.compression,
+                &archive_path,
+                &download_path,
+                executable_path,
+                strip_prefix.as_deref(),
+            )
+            .await?;
+            tokio::fs::remove_file(&archive_path).await.map_err(|e| {
+                format!("Failed to remove file {}, {}", archive_path.display(), e)
+            })?;
+            executable::make_executables(configuration, &download_path, os, arch).await?;
+            eprintln!("Done!");
+
+            Ok(())
+        },
+    ).await
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let file = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!(
-            "Usage: {} <path to json file>",
-            std::env::args().next().unwrap()
-        );
-        std::process::exit(1);
-    });
-
-    let downloader = UnauthenticatedDownloader::new();
-    let marker_manager = FileMarkerManager::new();
+    let tools_binding = shellexpand::tilde(TOOLS_LOCATION);
+    let tools_dir = Path::new(tools_binding.as_ref());
+    let command = invocation::parse_command(std::env::args().collect(), tools_dir)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+    let invocation = match command {
+        invocation::Command::Launch(invocation) => invocation,
+        invocation::Command::Shim(args) => return commands::shim::run(&args, tools_dir).await,
+        invocation::Command::Init(args) => return commands::init::run(&args).await,
+        invocation::Command::Bump(args) => return commands::bump::run(&args).await,
+    };
 
-    let configuration = read_configuration(&file).await?;
+    let mut configuration = read_configuration(&invocation.config_path).await?;
     let binding = shellexpand::tilde(CACHE_LOCATION);
     let cache_path = path::Path::new(binding.as_ref());
     let command_cache_base = cache_path.join(&configuration.name);
 
-    let download_path = command_cache_base.join("downloaded");
-    let executable_path = download_path.join(&configuration.name);
-    let sha256_marker_path = command_cache_base.join("sha256");
+    let os = std::env::consts::OS;
+    let arch = template::map_arch(std::env::consts::ARCH)?;
 
+    let download_path = command_cache_base.join("downloaded");
     if tokio::fs::metadata(&download_path).await.is_err() {
         tokio::fs::create_dir_all(&download_path)
             .await
@@ -86,52 +216,72 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 )
             })?;
     }
+    configuration.version =
+        version::resolve(&configuration, &command_cache_base.join("version")).await?;
 
-    let (url, sha256) = downloader
-        .build_url_and_sha256(&configuration, std::env::consts::OS, std::env::consts::ARCH)
-        .await?;
-
-    marker_manager.invoke_if_different(
-        sha256_marker_path,
-        &MarkerFile {
-            sha256: sha256.clone(),
-            url: url.clone(),
-        },
-        || async {
-            eprint!("Downloading {}...", configuration.name);
-
-            let content: Bytes = downloader
-                .download_and_validate_sha256(&url, &sha256)
-                .await?;
+    let args = invocation.args;
+    let (entrypoint, consumed_args) =
+        executable::select_entrypoint(&configuration, invocation.invoked_as.as_deref(), &args);
+    let executable_path = download_path.join(executable::resolve_executable(
+        &configuration,
+        entrypoint.as_deref(),
+        os,
+        arch,
+    )?);
 
-            extract_to_disk(
-                &configuration.compression,
-                &content,
-                &download_path,
+    match configuration.source {
+        Source::Github => {
+            let credential = github::credential(&configuration).await?;
+            let downloader = GithubDownloader::with_credential(credential);
+            install(
+                &downloader,
+                &configuration,
+                os,
+                arch,
+                &command_cache_base,
                 &executable_path,
             )
             .await?;
-            eprintln!("Done!");
-
-            Ok(())
-        },
-    ).await?;
-
-    let args = std::env::args().skip(2).collect::<Vec<_>>();
-    let mut command = Command::new(executable_path);
-    command.args(args);
-    command.stdin(std::process::Stdio::inherit());
-    command.stdout(std::process::Stdio::inherit());
-    command.stderr(std::process::Stdio::inherit());
-
-    for (key, value) in std::env::vars() {
-        command.env(key, value);
-    }
-
-    let status = command.status().await?;
-    if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        }
+        Source::Url => {
+            let url = providers::http_download::build_url(&configuration, os, arch)?;
+            let credential = credentials::CredentialSources::from_environment()
+                .resolve(configuration.credential.as_deref(), &url)
+                .await?;
+            match credential {
+                Some(credential) => {
+                    let downloader = AuthenticatedDownloader::with_credential(credential);
+                    install(
+                        &downloader,
+                        &configuration,
+                        os,
+                        arch,
+                        &command_cache_base,
+                        &executable_path,
+                    )
+                    .await?;
+                }
+                None => {
+                    let downloader = UnauthenticatedDownloader::new();
+                    install(
+                        &downloader,
+                        &configuration,
+                        os,
+                        arch,
+                        &command_cache_base,
+                        &executable_path,
+                    )
+                    .await?;
+                }
+            }
+        }
     }
 
-    Ok(())
-}
\ No newline at end of file
+    // Replace the launcher process rather than waiting on a child, so the
+    // tool keeps our PID, receives signals directly and reports its own exit
+    // status. Stdio and the environment are inherited as they are.
+    let error = std::process::Command::new(&executable_path)
+        .args(&args[consumed_args..])
+        .exec();
+    Err(format!("Failed to execute {}, {}", executable_path.display(), error).into())
+}
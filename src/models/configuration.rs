@@ -1,32 +1,271 @@
 use std::collections::BTreeMap;
 
+use crate::digest::{Algorithm, Digest};
+
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct File {
     pub name: String,
+    /// An exact version, `latest`, or a semver requirement such as `^1.11`
+    /// or `~1.5.0` resolved against `versions`.
     pub version: String,
-    #[serde(rename = "urlPattern")]
+    /// Where the artifact comes from, `url` unless stated otherwise.
+    #[serde(default)]
+    pub source: Source,
+    /// Required for the `url` source.
+    #[serde(
+        default,
+        rename = "urlPattern",
+        skip_serializing_if = "String::is_empty"
+    )]
     pub url_pattern: String,
+    /// `owner/name` of the repository, for the `github` source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Name of the release asset to download, templated and allowing `*`
+    /// wildcards, for the `github` source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+    /// Tag of the release, templated. Defaults to `v{{version}}`, then
+    /// `{{version}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Base URL of the GitHub REST API, for GitHub Enterprise or tests.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "apiUrl")]
+    pub api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub platforms: Platforms,
+    /// URL of a checksums file listing the digest of each artifact,
+    /// templated. Used instead of the per-platform digests, which cannot
+    /// cover versions resolved at launch.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "checksumsUrl")]
+    pub checksums_url: Option<String>,
+    /// Algorithm of the checksums file, when its hashes do not tell: BLAKE3
+    /// and SHA256 hashes have the same length.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "checksumsAlgorithm")]
+    pub checksums_algorithm: Option<Algorithm>,
+    /// Verifies the checksums file before any digest in it is trusted, such
+    /// as an OpenPGP `SHA256SUMS.sig`. The signature URL defaults to the
+    /// checksums URL followed by the usual suffix.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "checksumsSignature")]
+    pub checksums_signature: Option<Signature>,
+    /// Verifies who published the artifact, once its digest matches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+    /// Where the versions a requirement is resolved against are listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versions: Option<VersionSource>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "stripPrefix")]
     pub strip_prefix: Option<String>,
+    /// Path of the executable inside the extracted archive, defaults to `name`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "bin")]
+    pub executable: Option<String>,
+    /// Additional named executables shipped in the same archive.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub entrypoints: BTreeMap<String, String>,
+    #[serde(default)]
     pub compression: Compression,
+    #[serde(default, skip_serializing_if = "RetryConfiguration::is_default")]
+    pub retry: RetryConfiguration,
+    /// Name of the credential to download with, looked up in the environment
+    /// or the credentials file so the secret stays out of this file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Downloaded from `urlPattern`.
+    #[default]
+    Url,
+    /// An asset of a GitHub release of `repo`.
+    Github,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct Signature {
+    #[serde(default)]
+    pub format: SignatureFormat,
+    /// URL of the detached signature, templated, where `{{url}}` is the URL
+    /// of the artifact. Defaults to `{{url}}.minisig` or `{{url}}.sig`, or to
+    /// the release asset named that way for the `github` source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The key the artifact must be signed with: a minisign public key, an
+    /// Ed25519 one in base64, hex or PEM, or armored OpenPGP public keys.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "publicKey")]
+    pub public_key: Option<String>,
+    /// Path of a file holding the key instead, such as an OpenPGP keyring
+    /// exported by `gpg --export`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyring: Option<String>,
+    /// For cosign bundles, the identity the signing certificate must be
+    /// issued to: its email or URI, exactly, or a URI ending with a `/*`
+    /// path segment to accept anything under it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// For cosign bundles, the OIDC issuer that vouched for the identity,
+    /// such as `https://token.actions.githubusercontent.com`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// For cosign bundles, the certificate authority and transparency log
+    /// to trust, so that bundles verify without network access.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "trustRoot")]
+    pub trust_root: Option<TrustRoot>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct TrustRoot {
+    /// Certificates of the Fulcio root and intermediates, in PEM; the
+    /// signing certificate must chain to one of the roots.
+    pub certificates: String,
+    /// Public keys of the Rekor logs, in PEM; the bundle must carry an entry
+    /// timestamp signed by one of them.
+    #[serde(rename = "rekorKeys")]
+    pub rekor_keys: String,
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureFormat {
+    /// A `.minisig` file, prehashed or legacy.
+    #[default]
+    Minisign,
+    /// The 64 bytes of an Ed25519 signature of the artifact, raw or in
+    /// base64 or hex.
+    Ed25519,
+    /// An OpenPGP detached signature, binary or armored, as `gpg
+    /// --detach-sign` makes.
+    Openpgp,
+    /// A cosign bundle of a keyless signature, checked offline against the
+    /// `trustRoot`.
+    Cosign,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct VersionSource {
+    /// Defaults to `github` for the `github` source.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<VersionSourceType>,
+    /// The JSON index or directory listing, for those types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Stripped from listed versions, as in `go1.22.0` or `terraform_1.5.0`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// How long a resolved version is reused before listing versions again.
+    #[serde(default = "VersionSource::default_ttl_seconds", rename = "ttlSeconds")]
+    pub ttl_seconds: u64,
+}
+
+impl VersionSource {
+    pub fn default_ttl_seconds() -> u64 {
+        24 * 60 * 60
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionSourceType {
+    /// Tags of the releases of `repo`.
+    Github,
+    /// A JSON document: a list of versions, of objects with a `version`, or
+    /// an object with such a `versions` list or keyed by version.
+    Index,
+    /// An HTML or plain-text listing, with a link or line per version.
+    Listing,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryConfiguration {
+    /// How many times a failed download is retried before giving up.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each one after it.
+    #[serde(rename = "initialBackoffMs")]
+    pub initial_backoff_ms: u64,
+    #[serde(rename = "maxBackoffMs")]
+    pub max_backoff_ms: u64,
+    /// Fraction of each delay that is randomized, between 0 and 1.
+    pub jitter: f64,
+}
+
+impl Default for RetryConfiguration {
+    fn default() -> Self {
+        RetryConfiguration {
+            retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryConfiguration {
+    fn is_default(&self) -> bool {
+        *self == RetryConfiguration::default()
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Compression {
     #[serde(rename = "type")]
     pub compression_type: CompressionType,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum CompressionType {
+    /// The download is the executable itself.
+    #[default]
+    #[serde(alias = "binary")]
+    None,
+    /// A single gzip-compressed executable, not a tarball.
+    #[serde(alias = "gz")]
+    Gzip,
     Zip,
+    #[serde(rename = "tar.gz", alias = "tgz")]
+    TarGz,
+    #[serde(rename = "tar.xz", alias = "txz")]
+    TarXz,
+    #[serde(rename = "tar.bz2", alias = "tbz2")]
+    TarBz2,
+    #[serde(rename = "tar.zst", alias = "tzst")]
+    TarZst,
 }
 
 pub type Platforms = BTreeMap<String, PlatformOS>;
 pub type PlatformOS = BTreeMap<String, PlatformOSArchitecture>;
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
 pub struct PlatformOSArchitecture {
-    pub sha256: String,
+    /// One of the digests is required unless `checksumsUrl` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    /// A digest along with its algorithm, as in `sha512:<hex>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<Digest>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "bin")]
+    pub executable: Option<String>,
+}
+
+impl PlatformOSArchitecture {
+    /// The digest the artifact must have. When several are given, `digest`
+    /// wins, then the strongest algorithm.
+    pub fn digest(&self) -> Result<Option<Digest>, String> {
+        if let Some(digest) = &self.digest {
+            return Ok(Some(digest.clone()));
+        }
+        [
+            (Algorithm::Sha512, &self.sha512),
+            (Algorithm::Blake3, &self.blake3),
+            (Algorithm::Sha256, &self.sha256),
+        ]
+        .into_iter()
+        .find_map(|(algorithm, hex)| hex.as_deref().map(|hex| Digest::new(algorithm, hex)))
+        .transpose()
+    }
 }
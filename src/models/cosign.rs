@@ -0,0 +1,147 @@
+/// A cosign bundle: the Sigstore bundle `cosign sign-blob --bundle` writes
+/// since cosign 2.4, or the older format with a Rekor bundle.
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Bundle {
+    Sigstore(SigstoreBundle),
+    Legacy(LegacyBundle),
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigstoreBundle {
+    pub verification_material: VerificationMaterial,
+    pub message_signature: MessageSignature,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMaterial {
+    /// The signing certificate, in bundles from version 0.3 on.
+    pub certificate: Option<RawBytes>,
+    /// The signing certificate first, in earlier bundles.
+    pub x509_certificate_chain: Option<CertificateChain>,
+    #[serde(default)]
+    pub tlog_entries: Vec<TransparencyLogEntry>,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct CertificateChain {
+    pub certificates: Vec<RawBytes>,
+}
+
+/// DER bytes, in base64.
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RawBytes {
+    pub raw_bytes: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencyLogEntry {
+    /// 64 bit integers are strings in the JSON of protocol buffers.
+    pub log_index: String,
+    pub log_id: LogId,
+    pub integrated_time: String,
+    pub inclusion_promise: Option<InclusionPromise>,
+    /// The entry as Rekor stored it, in base64.
+    pub canonicalized_body: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogId {
+    /// SHA-256 of the public key of the log, in base64.
+    pub key_id: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionPromise {
+    pub signed_entry_timestamp: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSignature {
+    pub message_digest: Option<MessageDigest>,
+    pub signature: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct MessageDigest {
+    /// Such as `SHA2_256`.
+    pub algorithm: String,
+    pub digest: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct LegacyBundle {
+    #[serde(rename = "base64Signature")]
+    pub base64_signature: String,
+    /// The signing certificate in PEM, itself in base64.
+    pub cert: String,
+    #[serde(rename = "rekorBundle")]
+    pub rekor_bundle: RekorBundle,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct RekorBundle {
+    #[serde(rename = "SignedEntryTimestamp")]
+    pub signed_entry_timestamp: String,
+    #[serde(rename = "Payload")]
+    pub payload: RekorPayload,
+}
+
+/// What the signed entry timestamp of Rekor signs, as canonical JSON.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct RekorPayload {
+    /// The entry, in base64.
+    pub body: String,
+    #[serde(rename = "integratedTime")]
+    pub integrated_time: i64,
+    #[serde(rename = "logIndex")]
+    pub log_index: i64,
+    /// SHA-256 of the public key of the log, in hex.
+    #[serde(rename = "logID")]
+    pub log_id: String,
+}
+
+/// A `hashedrekord` entry of Rekor, recording a signature of a digest.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekord {
+    pub kind: String,
+    pub spec: HashedRekordSpec,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekordSpec {
+    pub data: HashedRekordData,
+    pub signature: HashedRekordSignature,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekordData {
+    pub hash: HashedRekordHash,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekordHash {
+    pub algorithm: String,
+    pub value: String,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekordSignature {
+    /// In base64.
+    pub content: String,
+    #[serde(rename = "publicKey")]
+    pub public_key: HashedRekordPublicKey,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct HashedRekordPublicKey {
+    /// The signing certificate in PEM, itself in base64.
+    pub content: String,
+}
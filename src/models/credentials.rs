@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+/// The credentials file, mapping credential names or hosts to credentials.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct CredentialsFile {
+    #[serde(default)]
+    pub credentials: BTreeMap<String, Credential>,
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct Credential {
+    /// Host the credential may be sent to, so that a configuration naming it
+    /// cannot leak it to another server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Sent as a bearer token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Sent with `password` as basic authentication, when there is no token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Extra headers, such as `X-JFrog-Art-Api`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
@@ -1,5 +1,9 @@
+use crate::digest::Digest;
+
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
 pub struct MarkerFile {
-    pub sha256: String,
+    /// What the download was validated against, with its algorithm so that
+    /// switching algorithms downloads again.
+    pub digest: Digest,
     pub url: String,
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+/// A release, as returned by the GitHub REST API.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+pub struct Asset {
+    pub name: String,
+    /// API URL of the asset, which serves its content when asked for
+    /// `application/octet-stream`, including for private repositories.
+    pub url: String,
+    pub browser_download_url: String,
+}
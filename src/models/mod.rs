@@ -1,3 +1,8 @@
-
 pub mod configuration;
-pub mod download_marker;
\ No newline at end of file
+pub mod cosign;
+pub mod credentials;
+pub mod download_marker;
+pub mod github;
+pub mod partial_download;
+pub mod resolved_artifact;
+pub mod resolved_version;
@@ -0,0 +1,8 @@
+/// Sidecar of a partially downloaded artifact, recording what is needed to
+/// resume it with an `If-Range` request.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct PartialDownload {
+    pub url: String,
+    /// A strong `ETag` or a `Last-Modified` date of the response.
+    pub validator: String,
+}
@@ -0,0 +1,12 @@
+use crate::digest::Digest;
+
+/// The URL and digest an artifact resolved to, cached so that launching does
+/// not query release APIs or checksums files every time.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct ResolvedArtifact {
+    /// Everything the resolution depends on, so that it is redone when the
+    /// configuration changes.
+    pub key: String,
+    pub url: String,
+    pub digest: Digest,
+}
@@ -0,0 +1,13 @@
+/// A version requirement resolved against a version source, cached so that
+/// launching does not list versions every time.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct ResolvedVersion {
+    /// The `version` of the configuration, such as `latest` or `^1.11`.
+    pub requirement: String,
+    /// Where the versions were listed.
+    pub source: String,
+    pub version: String,
+    /// Seconds since the Unix epoch.
+    #[serde(rename = "resolvedAt")]
+    pub resolved_at: u64,
+}
@@ -0,0 +1,1257 @@
+//! Just enough OpenPGP (RFC 4880, RFC 9580) to check a detached signature,
+//! such as `SHA256SUMS.sig`, against pinned public keys: v4 keys and
+//! signatures made with RSA or Ed25519.
+//!
+//! A key is only trusted while its self-signatures vouch for it: a primary
+//! key needs a self-signature, a subkey a binding signature from its primary
+//! key with a back-signature, and neither may be revoked, expired or flagged
+//! as unable to sign. Revocations by designated revokers are not considered.
+//! Signatures that have expired, or that carry a critical subpacket not
+//! understood here, are rejected.
+
+use std::io::Read;
+
+use base64::Engine;
+use sha2::Digest;
+
+use crate::signature;
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+const TYPE_BINARY: u8 = 0x00;
+const TYPE_TEXT: u8 = 0x01;
+const TYPE_CERTIFICATIONS: std::ops::RangeInclusive<u8> = 0x10..=0x13;
+const TYPE_SUBKEY_BINDING: u8 = 0x18;
+const TYPE_PRIMARY_KEY_BINDING: u8 = 0x19;
+const TYPE_DIRECT_KEY: u8 = 0x1f;
+const TYPE_KEY_REVOCATION: u8 = 0x20;
+const TYPE_SUBKEY_REVOCATION: u8 = 0x28;
+
+const ALGORITHM_RSA: u8 = 1;
+const ALGORITHM_RSA_SIGN_ONLY: u8 = 3;
+const ALGORITHM_EDDSA_LEGACY: u8 = 22;
+const ALGORITHM_ED25519: u8 = 27;
+
+const SUBPACKET_CREATION_TIME: u8 = 2;
+const SUBPACKET_EXPIRATION_TIME: u8 = 3;
+const SUBPACKET_KEY_EXPIRATION_TIME: u8 = 9;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_KEY_FLAGS: u8 = 27;
+const SUBPACKET_EMBEDDED_SIGNATURE: u8 = 32;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// The subpackets that are understood, so that they may be marked critical.
+const KNOWN_SUBPACKETS: [u8; 7] = [
+    SUBPACKET_CREATION_TIME,
+    SUBPACKET_EXPIRATION_TIME,
+    SUBPACKET_KEY_EXPIRATION_TIME,
+    SUBPACKET_ISSUER,
+    SUBPACKET_KEY_FLAGS,
+    SUBPACKET_EMBEDDED_SIGNATURE,
+    SUBPACKET_ISSUER_FINGERPRINT,
+];
+
+const KEY_FLAG_SIGN: u8 = 0x02;
+
+/// OID of Ed25519 in legacy EdDSA keys.
+const ED25519_OID: [u8; 9] = [0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+
+/// Checks that one of the signatures in `signature`, armored or binary, was
+/// made over `content` by one of the public keys in `keys`, armored or
+/// binary, as exported by `gpg --export`.
+pub fn verify(keys: &[u8], content: impl Read, signature: &[u8]) -> Result<(), String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    verify_at(keys, content, signature, now)
+}
+
+/// Like `verify`, with the keys judged as of `now`, in seconds since the
+/// epoch.
+fn verify_at(keys: &[u8], content: impl Read, signature: &[u8], now: u64) -> Result<(), String> {
+    let public_keys = parse_certificates(&dearmor(keys)?, now)?;
+    if public_keys.is_empty() {
+        return Err("No OpenPGP public key found".to_string());
+    }
+
+    let mut error = String::new();
+    let signatures: Vec<Signature> = packets(&dearmor(signature)?)?
+        .into_iter()
+        .filter(|packet| packet.tag == TAG_SIGNATURE)
+        .filter_map(|packet| parse_signature(packet.body).map_err(|e| error = e).ok())
+        .collect();
+    if signatures.is_empty() {
+        if error.is_empty() {
+            error = "No OpenPGP signature found".to_string();
+        }
+        return Err(error);
+    }
+
+    // The content is read once, into the hash each signature is made with.
+    let mut hashes: Vec<Result<ContentHash, String>> =
+        signatures.iter().map(ContentHash::new).collect();
+    signature::read_chunks(content, |chunk| {
+        for hash in hashes.iter_mut().flatten() {
+            hash.update(chunk);
+        }
+    })?;
+
+    for (signature, hash) in signatures.iter().zip(&hashes) {
+        if signature.has_expired(now) {
+            error = "Signature has expired".to_string();
+            continue;
+        }
+        let keys: Vec<&PublicKey> = public_keys
+            .iter()
+            .filter(|key| {
+                signature.issuers.is_empty()
+                    || signature.issuers.iter().any(|issuer| key.is(issuer))
+            })
+            .collect();
+        if keys.is_empty() {
+            error = format!(
+                "Signed by key {}, which is not trusted",
+                signature
+                    .issuers
+                    .first()
+                    .map(|issuer| hex(issuer))
+                    .unwrap_or_default()
+            );
+            continue;
+        }
+        for key in keys {
+            if let Some(reason) = &key.unusable {
+                error = reason.clone();
+                continue;
+            }
+            let result = match hash {
+                Ok(hash) => signature.verify_hashed(key, hash.hasher.box_clone()),
+                Err(e) => Err(e.clone()),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => error = e,
+            }
+        }
+    }
+    Err(error)
+}
+
+struct Packet<'a> {
+    tag: u8,
+    body: &'a [u8],
+}
+
+struct PublicKey {
+    fingerprint: [u8; 20],
+    /// In seconds since the epoch.
+    created: u32,
+    material: KeyMaterial,
+    /// The packet body, which signatures binding the key are made over.
+    body: Vec<u8>,
+    /// Why signatures made by the key are not trusted, if they are not.
+    unusable: Option<String>,
+}
+
+enum KeyMaterial {
+    Rsa {
+        n: Vec<u8>,
+        e: Vec<u8>,
+    },
+    Ed25519([u8; 32]),
+    /// Another algorithm, kept so that a signature it made is reported as such.
+    Unsupported(u8),
+}
+
+impl PublicKey {
+    /// Whether `issuer`, a key ID or a fingerprint, names this key.
+    fn is(&self, issuer: &[u8]) -> bool {
+        issuer == self.fingerprint || issuer == &self.fingerprint[12..]
+    }
+
+    /// The key as it is hashed into signatures over it.
+    fn hashed(&self) -> Vec<u8> {
+        let mut hashed = vec![0x99];
+        hashed.extend_from_slice(&(self.body.len() as u16).to_be_bytes());
+        hashed.extend_from_slice(&self.body);
+        hashed
+    }
+
+    /// Whether the validity that `signature` gives the key has run out.
+    fn has_expired(&self, signature: &Signature, now: u64) -> bool {
+        matches!(signature.key_expiration, Some(seconds)
+            if seconds != 0 && self.created as u64 + seconds as u64 <= now)
+    }
+
+    /// Why `signature`, the latest self-signature of the key, does not let it
+    /// make signatures, if it does not.
+    fn check_validity(&self, signature: &Signature, now: u64) -> Option<String> {
+        if self.has_expired(signature, now) {
+            return Some(format!("Key {} has expired", hex(&self.fingerprint)));
+        }
+        if signature
+            .key_flags
+            .is_some_and(|flags| flags & KEY_FLAG_SIGN == 0)
+        {
+            return Some(format!(
+                "Key {} is not allowed to sign",
+                hex(&self.fingerprint)
+            ));
+        }
+        None
+    }
+}
+
+struct Signature {
+    signature_type: u8,
+    algorithm: u8,
+    hash_algorithm: u8,
+    /// In seconds since the epoch, zero when missing.
+    created: u32,
+    /// Seconds from its creation until the signature expires.
+    expiration: Option<u32>,
+    /// Seconds from the creation of the key it is over until that expires.
+    key_expiration: Option<u32>,
+    key_flags: Option<u8>,
+    /// Back-signatures of subkeys, in their binding signatures.
+    embedded: Vec<Signature>,
+    /// From the version through the hashed subpackets, which are signed along
+    /// with the content.
+    hashed: Vec<u8>,
+    /// Key IDs and fingerprints of the key that made it.
+    issuers: Vec<Vec<u8>>,
+    left_16: [u8; 2],
+    values: Vec<Vec<u8>>,
+}
+
+impl Signature {
+    /// Whether the signature is no longer valid at `now`.
+    fn has_expired(&self, now: u64) -> bool {
+        matches!(self.expiration, Some(seconds)
+            if seconds != 0 && self.created as u64 + seconds as u64 <= now)
+    }
+
+    /// Whether `key` made this signature, of `signature_type`, over `parts`
+    /// no later than `now`, and it has not expired since.
+    fn certifies(&self, signature_type: u8, key: &PublicKey, parts: &[&[u8]], now: u64) -> bool {
+        self.signature_type == signature_type
+            && self.created as u64 <= now
+            && !self.has_expired(now)
+            && (self.issuers.is_empty() || self.issuers.iter().any(|issuer| key.is(issuer)))
+            && self.verify_over(key, parts).is_ok()
+    }
+
+    /// Checks the signature made by `key` over the concatenation of `parts`.
+    fn verify_over(&self, key: &PublicKey, parts: &[&[u8]]) -> Result<(), String> {
+        let mut hasher = hasher(self.hash_algorithm)?;
+        for part in parts {
+            hasher.update(part);
+        }
+        self.verify_hashed(key, hasher)
+    }
+
+    /// Checks the signature made by `key` over what `hasher` has been fed.
+    fn verify_hashed(
+        &self,
+        key: &PublicKey,
+        mut hasher: Box<dyn sha2::digest::DynDigest>,
+    ) -> Result<(), String> {
+        hasher.update(&self.hashed);
+        hasher.update(&[0x04, 0xff]);
+        hasher.update(&(self.hashed.len() as u32).to_be_bytes());
+        let digest = hasher.finalize();
+        if digest[..2] != self.left_16 {
+            return Err("Signature does not match".to_string());
+        }
+
+        match (&key.material, self.algorithm, self.values.as_slice()) {
+            (KeyMaterial::Rsa { n, e }, ALGORITHM_RSA | ALGORITHM_RSA_SIGN_ONLY, [signature]) => {
+                verify_rsa(n, e, self.hash_algorithm, &digest, signature)
+            }
+            (KeyMaterial::Ed25519(key), ALGORITHM_EDDSA_LEGACY | ALGORITHM_ED25519, values) => {
+                verify_ed25519(key, &digest, values)
+            }
+            (KeyMaterial::Unsupported(algorithm), _, _) => Err(format!(
+                "Unsupported OpenPGP public key algorithm {}",
+                algorithm
+            )),
+            _ => Err("Signature does not match the algorithm of the key".to_string()),
+        }
+    }
+}
+
+fn verify_rsa(
+    n: &[u8],
+    e: &[u8],
+    hash_algorithm: u8,
+    digest: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    const MIN_BITS: usize = 2048;
+    const MAX_BITS: usize = 16384;
+    let key = rsa::RsaPublicKey::new_with_max_size(
+        rsa::BigUint::from_bytes_be(n),
+        rsa::BigUint::from_bytes_be(e),
+        MAX_BITS,
+    )
+    .map_err(|e| format!("Invalid RSA public key, {}", e))?;
+    if rsa::traits::PublicKeyParts::size(&key) * 8 < MIN_BITS {
+        return Err(format!("RSA keys under {} bits are not trusted", MIN_BITS));
+    }
+    let scheme = match hash_algorithm {
+        8 => rsa::Pkcs1v15Sign::new::<sha2::Sha256>(),
+        9 => rsa::Pkcs1v15Sign::new::<sha2::Sha384>(),
+        10 => rsa::Pkcs1v15Sign::new::<sha2::Sha512>(),
+        11 => rsa::Pkcs1v15Sign::new::<sha2::Sha224>(),
+        other => return Err(format!("Unsupported OpenPGP hash algorithm {}", other)),
+    };
+    let signature = left_pad(signature, rsa::traits::PublicKeyParts::size(&key))
+        .ok_or("Signature does not match")?;
+    key.verify(scheme, digest, &signature)
+        .map_err(|_| "Signature does not match".to_string())
+}
+
+fn verify_ed25519(key: &[u8; 32], digest: &[u8], values: &[Vec<u8>]) -> Result<(), String> {
+    let signature: [u8; 64] = match values {
+        // R and S as numbers in legacy EdDSA signatures, which may have lost
+        // their leading zeros.
+        [r, s] => left_pad(r, 32)
+            .zip(left_pad(s, 32))
+            .and_then(|(r, s)| [r, s].concat().try_into().ok()),
+        [signature] => signature.as_slice().try_into().ok(),
+        _ => None,
+    }
+    .ok_or("Invalid Ed25519 signature")?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(key)
+        .map_err(|e| format!("Invalid Ed25519 public key, {}", e))?;
+    key.verify_strict(digest, &ed25519_dalek::Signature::from_bytes(&signature))
+        .map_err(|_| "Signature does not match".to_string())
+}
+
+fn hasher(algorithm: u8) -> Result<Box<dyn sha2::digest::DynDigest>, String> {
+    match algorithm {
+        8 => Ok(Box::new(sha2::Sha256::new())),
+        9 => Ok(Box::new(sha2::Sha384::new())),
+        10 => Ok(Box::new(sha2::Sha512::new())),
+        11 => Ok(Box::new(sha2::Sha224::new())),
+        2 => Err("SHA1 OpenPGP signatures are not trusted".to_string()),
+        other => Err(format!("Unsupported OpenPGP hash algorithm {}", other)),
+    }
+}
+
+/// The hash of the content a signature is made over, fed as it is read.
+struct ContentHash {
+    hasher: Box<dyn sha2::digest::DynDigest>,
+    /// Text signatures are made over the content with CRLF line endings.
+    text: bool,
+    /// Whether the last byte fed was a carriage return.
+    after_cr: bool,
+}
+
+impl ContentHash {
+    fn new(signature: &Signature) -> Result<ContentHash, String> {
+        let text = match signature.signature_type {
+            TYPE_BINARY => false,
+            TYPE_TEXT => true,
+            other => return Err(format!("Unsupported OpenPGP signature type {}", other)),
+        };
+        Ok(ContentHash {
+            hasher: hasher(signature.hash_algorithm)?,
+            text,
+            after_cr: false,
+        })
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        if !self.text {
+            self.hasher.update(chunk);
+            return;
+        }
+        let mut start = 0;
+        for (index, byte) in chunk.iter().enumerate() {
+            if *byte == b'\n' && !self.after_cr {
+                self.hasher.update(&chunk[start..index]);
+                self.hasher.update(b"\r");
+                start = index;
+            }
+            self.after_cr = *byte == b'\r';
+        }
+        self.hasher.update(&chunk[start..]);
+    }
+}
+
+fn left_pad(value: &[u8], length: usize) -> Option<Vec<u8>> {
+    (value.len() <= length).then(|| [vec![0; length - value.len()], value.to_vec()].concat())
+}
+
+/// The binary packets of `data`, decoding any ASCII armor blocks in it.
+fn dearmor(data: &[u8]) -> Result<Vec<u8>, String> {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return Ok(data.to_vec());
+    };
+    if !text.contains("-----BEGIN PGP ") {
+        return Ok(data.to_vec());
+    }
+
+    let mut binary = Vec::new();
+    let mut lines = text.lines().map(str::trim);
+    while lines.any(|line| line.starts_with("-----BEGIN PGP ")) {
+        let block: Vec<&str> = lines
+            .by_ref()
+            .take_while(|line| !line.starts_with("-----END PGP "))
+            .collect();
+        // Armor headers such as `Comment: ...` end with a blank line.
+        let start = block
+            .iter()
+            .position(|line| line.is_empty())
+            .map_or(0, |blank| blank + 1);
+        let body: String = block[start..]
+            .iter()
+            // The CRC24 checksum, redundant with the signature itself.
+            .filter(|line| !(line.starts_with('=') && line.len() == 5))
+            .copied()
+            .collect();
+        binary.extend(
+            base64::engine::general_purpose::STANDARD
+                .decode(&body)
+                .map_err(|e| format!("Invalid OpenPGP armor, {}", e))?,
+        );
+    }
+    Ok(binary)
+}
+
+fn packets(data: &[u8]) -> Result<Vec<Packet<'_>>, String> {
+    let invalid = || "Invalid OpenPGP packet".to_string();
+    let mut packets = Vec::new();
+    let mut reader = Reader(data);
+    while !reader.0.is_empty() {
+        let header = reader.byte()?;
+        if header & 0x80 == 0 {
+            return Err(invalid());
+        }
+        let (tag, length) = if header & 0x40 != 0 {
+            let first = reader.byte()? as usize;
+            let length = match first {
+                0..192 => first,
+                192..224 => ((first - 192) << 8) + reader.byte()? as usize + 192,
+                255 => reader.u32()? as usize,
+                _ => return Err("Partial OpenPGP packet lengths are not supported".to_string()),
+            };
+            (header & 0x3f, length)
+        } else {
+            let length = match header & 0x03 {
+                0 => reader.byte()? as usize,
+                1 => reader.u16()? as usize,
+                2 => reader.u32()? as usize,
+                _ => reader.0.len(),
+            };
+            ((header >> 2) & 0x0f, length)
+        };
+        packets.push(Packet {
+            tag,
+            body: reader.take(length)?,
+        });
+    }
+    Ok(packets)
+}
+
+/// The primary keys and subkeys of the certificates in `data`, as `gpg
+/// --export` writes them, each marked unusable unless the signatures after it
+/// vouch for it as of `now`.
+fn parse_certificates(data: &[u8], now: u64) -> Result<Vec<PublicKey>, String> {
+    let packets = packets(data)?;
+    let mut keys = Vec::new();
+    let mut start = 0;
+    while let Some(offset) = packets[start..]
+        .iter()
+        .position(|packet| packet.tag == TAG_PUBLIC_KEY)
+    {
+        let primary = start + offset;
+        let end = packets[primary + 1..]
+            .iter()
+            .position(|packet| packet.tag == TAG_PUBLIC_KEY)
+            .map_or(packets.len(), |next| primary + 1 + next);
+        keys.extend(parse_certificate(&packets[primary..end], now));
+        start = end;
+    }
+    Ok(keys)
+}
+
+/// The primary key that starts `packets` and its subkeys.
+fn parse_certificate(packets: &[Packet], now: u64) -> Vec<PublicKey> {
+    /// What the signatures that follow are over.
+    enum Component {
+        PrimaryKey,
+        /// A user ID or user attribute, as hashed into its certifications.
+        User(Vec<u8>),
+        /// The subkey at this index of `subkeys`.
+        Subkey(usize),
+        /// A subkey of an unsupported version.
+        Unsupported,
+    }
+
+    let Some(mut primary) = parse_key(packets[0].body) else {
+        return Vec::new();
+    };
+    let primary_hashed = primary.hashed();
+    let mut revoked = false;
+    let mut self_signatures = Vec::new();
+    // Each subkey with its latest binding signature and whether it is revoked.
+    let mut subkeys: Vec<(PublicKey, Option<Signature>, bool)> = Vec::new();
+    let mut component = Component::PrimaryKey;
+    for packet in &packets[1..] {
+        match packet.tag {
+            TAG_USER_ID | TAG_USER_ATTRIBUTE => {
+                let mut hashed = vec![if packet.tag == TAG_USER_ID {
+                    0xb4
+                } else {
+                    0xd1
+                }];
+                hashed.extend_from_slice(&(packet.body.len() as u32).to_be_bytes());
+                hashed.extend_from_slice(packet.body);
+                component = Component::User(hashed);
+            }
+            TAG_PUBLIC_SUBKEY => {
+                component = match parse_key(packet.body) {
+                    Some(subkey) => {
+                        subkeys.push((subkey, None, false));
+                        Component::Subkey(subkeys.len() - 1)
+                    }
+                    None => Component::Unsupported,
+                };
+            }
+            TAG_SIGNATURE => {
+                let Ok(signature) = parse_signature(packet.body) else {
+                    continue;
+                };
+                match &component {
+                    Component::PrimaryKey => {
+                        if signature.certifies(
+                            TYPE_KEY_REVOCATION,
+                            &primary,
+                            &[&primary_hashed],
+                            now,
+                        ) {
+                            revoked = true;
+                        } else if signature.certifies(
+                            TYPE_DIRECT_KEY,
+                            &primary,
+                            &[&primary_hashed],
+                            now,
+                        ) {
+                            self_signatures.push(signature);
+                        }
+                    }
+                    Component::User(hashed) => {
+                        if TYPE_CERTIFICATIONS.contains(&signature.signature_type)
+                            && signature.certifies(
+                                signature.signature_type,
+                                &primary,
+                                &[&primary_hashed, hashed],
+                                now,
+                            )
+                        {
+                            self_signatures.push(signature);
+                        }
+                    }
+                    Component::Subkey(index) => {
+                        let (subkey, binding, subkey_revoked) = &mut subkeys[*index];
+                        let subkey_hashed = subkey.hashed();
+                        let parts: [&[u8]; 2] = [&primary_hashed, &subkey_hashed];
+                        if signature.certifies(TYPE_SUBKEY_REVOCATION, &primary, &parts, now) {
+                            *subkey_revoked = true;
+                        } else if signature.certifies(TYPE_SUBKEY_BINDING, &primary, &parts, now)
+                            && binding
+                                .as_ref()
+                                .is_none_or(|binding| binding.created <= signature.created)
+                        {
+                            *binding = Some(signature);
+                        }
+                    }
+                    Component::Unsupported => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let fingerprint = hex(&primary.fingerprint);
+    let latest = self_signatures
+        .iter()
+        .max_by_key(|signature| signature.created);
+    // Why neither the primary key nor its subkeys are trusted, if they are not.
+    let invalid = match latest {
+        _ if revoked => Some(format!("Key {} has been revoked", fingerprint)),
+        None => Some(format!("Key {} has no valid self-signature", fingerprint)),
+        Some(signature) if primary.has_expired(signature, now) => {
+            Some(format!("Key {} has expired", fingerprint))
+        }
+        Some(_) => None,
+    };
+    primary.unusable = invalid
+        .clone()
+        .or_else(|| latest.and_then(|signature| primary.check_validity(signature, now)));
+
+    let mut keys = vec![primary];
+    for (mut subkey, binding, subkey_revoked) in subkeys {
+        let subkey_fingerprint = hex(&subkey.fingerprint);
+        subkey.unusable = if invalid.is_some() {
+            invalid.clone()
+        } else if subkey_revoked {
+            Some(format!("Key {} has been revoked", subkey_fingerprint))
+        } else {
+            match binding {
+                Some(binding) if backsigned(&binding, &primary_hashed, &subkey, now) => {
+                    subkey.check_validity(&binding, now)
+                }
+                _ => Some(format!(
+                    "Key {} is not bound to its primary key",
+                    subkey_fingerprint
+                )),
+            }
+        };
+        keys.push(subkey);
+    }
+    keys
+}
+
+/// Whether the subkey `binding` binds has signed it back, as a signing subkey
+/// must so that nobody can claim it as theirs.
+fn backsigned(binding: &Signature, primary_hashed: &[u8], subkey: &PublicKey, now: u64) -> bool {
+    binding.embedded.iter().any(|signature| {
+        signature.certifies(
+            TYPE_PRIMARY_KEY_BINDING,
+            subkey,
+            &[primary_hashed, &subkey.hashed()],
+            now,
+        )
+    })
+}
+
+/// A v4 public key or subkey; keys of other versions are skipped.
+fn parse_key(body: &[u8]) -> Option<PublicKey> {
+    let mut reader = Reader(body);
+    if reader.byte().ok()? != 4 {
+        return None;
+    }
+    let created = u32::from_be_bytes(reader.take(4).ok()?.try_into().ok()?);
+    let algorithm = reader.byte().ok()?;
+    let material = match algorithm {
+        ALGORITHM_RSA | ALGORITHM_RSA_SIGN_ONLY => KeyMaterial::Rsa {
+            n: reader.mpi().ok()?.to_vec(),
+            e: reader.mpi().ok()?.to_vec(),
+        },
+        ALGORITHM_EDDSA_LEGACY => {
+            let oid_length = reader.byte().ok()? as usize;
+            let oid = reader.take(oid_length).ok()?;
+            // A 0x40 prefix, then the native key.
+            match reader.mpi().ok()? {
+                [0x40, key @ ..] if oid == ED25519_OID => {
+                    KeyMaterial::Ed25519(key.try_into().ok()?)
+                }
+                _ => KeyMaterial::Unsupported(algorithm),
+            }
+        }
+        ALGORITHM_ED25519 => KeyMaterial::Ed25519(reader.take(32).ok()?.try_into().ok()?),
+        other => KeyMaterial::Unsupported(other),
+    };
+
+    let mut hasher = sha1::Sha1::new();
+    hasher.update([0x99]);
+    hasher.update((body.len() as u16).to_be_bytes());
+    hasher.update(body);
+    Some(PublicKey {
+        fingerprint: hasher.finalize().into(),
+        created,
+        material,
+        body: body.to_vec(),
+        unusable: None,
+    })
+}
+
+fn parse_signature(body: &[u8]) -> Result<Signature, String> {
+    let mut reader = Reader(body);
+    let version = reader.byte()?;
+    if version != 4 {
+        return Err(format!("Unsupported OpenPGP signature version {}", version));
+    }
+    let signature_type = reader.byte()?;
+    let algorithm = reader.byte()?;
+    let hash_algorithm = reader.byte()?;
+    let hashed_length = reader.u16()? as usize;
+    let hashed_subpackets = reader.take(hashed_length)?;
+    let unhashed_length = reader.u16()? as usize;
+    let unhashed_subpackets = reader.take(unhashed_length)?;
+    let left_16 = reader
+        .take(2)?
+        .try_into()
+        .map_err(|_| "Invalid OpenPGP packet")?;
+    let values = match algorithm {
+        ALGORITHM_ED25519 => vec![reader.take(64)?.to_vec()],
+        ALGORITHM_EDDSA_LEGACY => vec![reader.mpi()?.to_vec(), reader.mpi()?.to_vec()],
+        _ => vec![reader.mpi()?.to_vec()],
+    };
+
+    let mut created = 0;
+    let mut expiration = None;
+    let mut key_expiration = None;
+    let mut key_flags = None;
+    let mut issuers = Vec::new();
+    let mut embedded = Vec::new();
+    for (subpackets, is_hashed) in [(hashed_subpackets, true), (unhashed_subpackets, false)] {
+        for (kind, critical, data) in subpackets_of(subpackets)? {
+            // RFC 4880 5.2.3.1: a signature with a critical subpacket that is
+            // not understood is invalid.
+            if critical && !KNOWN_SUBPACKETS.contains(&kind) {
+                return Err(format!("Unsupported critical OpenPGP subpacket {}", kind));
+            }
+            match (kind, data, is_hashed) {
+                (SUBPACKET_CREATION_TIME, &[a, b, c, d], true) => {
+                    created = u32::from_be_bytes([a, b, c, d])
+                }
+                (SUBPACKET_EXPIRATION_TIME, &[a, b, c, d], true) => {
+                    expiration = Some(u32::from_be_bytes([a, b, c, d]))
+                }
+                (SUBPACKET_KEY_EXPIRATION_TIME, &[a, b, c, d], true) => {
+                    key_expiration = Some(u32::from_be_bytes([a, b, c, d]))
+                }
+                (SUBPACKET_KEY_FLAGS, [flags, ..], true) => key_flags = Some(*flags),
+                (SUBPACKET_ISSUER, _, _) => issuers.push(data.to_vec()),
+                (SUBPACKET_ISSUER_FINGERPRINT, [4, fingerprint @ ..], _) => {
+                    issuers.push(fingerprint.to_vec())
+                }
+                // A back-signature verifies on its own, wherever it is.
+                (SUBPACKET_EMBEDDED_SIGNATURE, _, _) => {
+                    if let Ok(signature) = parse_signature(data) {
+                        embedded.push(signature);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Signature {
+        signature_type,
+        algorithm,
+        hash_algorithm,
+        created,
+        expiration,
+        key_expiration,
+        key_flags,
+        embedded,
+        hashed: body[..6 + hashed_length].to_vec(),
+        issuers,
+        left_16,
+        values,
+    })
+}
+
+/// The type of a subpacket, without its critical bit, whether it is critical,
+/// and its data.
+type Subpacket<'a> = (u8, bool, &'a [u8]);
+
+fn subpackets_of(data: &[u8]) -> Result<Vec<Subpacket<'_>>, String> {
+    let mut subpackets = Vec::new();
+    let mut reader = Reader(data);
+    while !reader.0.is_empty() {
+        let first = reader.byte()? as usize;
+        let length = match first {
+            0..192 => first,
+            192..255 => ((first - 192) << 8) + reader.byte()? as usize + 192,
+            _ => reader.u32()? as usize,
+        };
+        let subpacket = reader.take(length)?;
+        let (kind, data) = subpacket.split_first().ok_or("Invalid OpenPGP subpacket")?;
+        subpackets.push((kind & 0x7f, kind & 0x80 != 0, data));
+    }
+    Ok(subpackets)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < length {
+            return Err("Truncated OpenPGP packet".to_string());
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(
+            self.take(2)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(
+            self.take(4)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    /// A multiprecision integer: its length in bits, then its bytes.
+    fn mpi(&mut self) -> Result<&'a [u8], String> {
+        let bits = self.u16()? as usize;
+        self.take(bits.div_ceil(8))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) static CONTENT: &str = concat!(
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        "  tool_1.0.0_linux_amd64.zip\n"
+    );
+
+    // Made with `gpg --quick-gen-key ... rsa2048` and `gpg --detach-sign`.
+    pub(crate) static RSA_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mQENBGrUE4cBCADjRjKP9FzWVwyyKGU5RkLYazw9bDzqUrxWPxOqEPZ6ueJobtGr
+9onTgG0WwP0aV7zKrxhShZLSxgMH3LZX0Wa6gzU1HG/Ucim+QKjTy0X3jwbbp7Un
+HE09RXo8koNPHVI2M91ZCUGspE6/PyWdPBLyrOmMPC/Gmc2maKOZaESxi7OQ3Tr8
+txrPFdl2YRQ3AdQVAVBblEh7TWOrvruFh+qqRPHu5eivo+sMJZ62i9Tqk9g+O2qE
+VhOMo5Z8uPQlAWntOQyy8DY/Alv54bvEsHV4eLiRjQEiKcbf3W8GWBRDc+6J1laP
+F4qiDw22+n4lbg5dvXChULhDD4Vvrfsrw1t1ABEBAAG0I0xhdW5jaGVyIFRlc3Qg
+UlNBIDxyc2FAZXhhbXBsZS5jb20+iQFOBBMBCgA4FiEEtuUOVX4R3EacuEwlTlsL
+VMEE3IYFAmrUE4cCGwMFCwkIBwIGFQoJCAsCBBYCAwECHgECF4AACgkQTlsLVMEE
+3IY00wgAtqF7208h77CT/K6ghK/BIJG2gx5vQIp61AW5ZQxE7qDv/JmNknCY9Cj/
+BmgSDxguHOMuPsrpx1Lzcd63Bs902RApoXmWJX8DEa6ktEVUBsgGZzFLed0xLmZz
+CZ+36zxhEPHnLyZbJ7em7mVI1AQoFVKnFyAm14J2a/vEDE1fmsyP9XLG9f8MDxUK
+D2HXO15D+BnXhIcoq6b8XmU6s76iPZlNg5uONLSlQQwspzdKK0eEE5qTM3Vyxhb1
+HyvSgMkFIffIG4uFOw+cUOz30FPoLoo0LAL8AbIXapOV2AxvLw4iQZy7s5z+ABqt
+XRjTSwqZGHemtwjl2wYaFR9A0p7/Mw==
+=yIml
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    pub(crate) static RSA_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iQFEBAABCgAuFiEEtuUOVX4R3EacuEwlTlsLVMEE3IYFAmrUE4gQHHJzYUBleGFt
+cGxlLmNvbQAKCRBOWwtUwQTchoVZB/wM3uzgIPkPQso9VbLkYsPLea//cpV2SgKs
+dpjyRxc7hDPhq/Wnqkwk6GWZvShkP5VWNGyZZnKceSyvxsUTECUE/+cfyFpufIov
+5/T7Wd9nLgl6MUBUYLsV1nfhrJKGRoyb1dZw3p5RO48IhKol9Nz781mOPqpAeIHQ
+rW6GUC+ijrVsXBK9u5PQrgh5bTomcFkAqPpUbCakpPFBtnP2sGlcEuzYVd3CYqff
+dv/mafQICam+dOs0lcCpNE8RHo317CKY/ljy9PVRkkE1Xfwz1tA4LY0XsftK6KNJ
+hq/BvJDCz7bmCWCtrEvaGbwFqqNe+v3GPU3Ghj2YBIj6ZJLuJoFf
+=i9If
+-----END PGP SIGNATURE-----
+";
+
+    // Made with `gpg --quick-gen-key ... ed25519` and `gpg --textmode
+    // --detach-sign`.
+    static ED25519_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatQThxYJKwYBBAHaRw8BAQdA0pk/IQJISGGLE2wX4tMnYYWmgAeLFeWxro9W
+JatUh3W0JkxhdW5jaGVyIFRlc3QgRWQyNTUxOSA8ZWRAZXhhbXBsZS5jb20+iJAE
+ExYIADgWIQSzCLsvgmvMeTzWDxmFYjVUlljVjwUCatQThwIbAwULCQgHAgYVCgkI
+CwIEFgIDAQIeAQIXgAAKCRCFYjVUlljVj1luAP9XClQTy2YLU+8E56dH2zUpivt9
+3C8yXXPOpPO30eCZtAD9Goqg08+8+yGJog3oiQx/9q3YZSp/ztojhY2y+ChGiwk=
+=0cu6
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    static ED25519_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iIUEARYIAC0WIQSzCLsvgmvMeTzWDxmFYjVUlljVjwUCatQTiA8cZWRAZXhhbXBs
+ZS5jb20ACgkQhWI1VJZY1Y/siwD/bt5VMwDZ5lKYb1sNYc9B0PeZUiybv3Eko8p0
+Pc6P3wgBAJVSo16leFryW2S6LVaOtOx4q9OyX+mJgPIi/k7Q/ogG
+=qbLg
+-----END PGP SIGNATURE-----
+";
+
+    // Made with `gpg --quick-gen-key ... ed25519 cert`, `gpg --quick-add-key
+    // ... ed25519 sign` and `gpg -u <subkey>! --detach-sign`.
+    static SUBKEY_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatQbsRYJKwYBBAHaRw8BAQdAi0s08y04rNcLWLLzaIYXnzsMvd+lzB0cvkpR
+BJPkFdu0JkxhdW5jaGVyIFRlc3QgU3Via2V5IDxzdWJAZXhhbXBsZS5jb20+iJAE
+ExYIADgWIQQhWYrXtz1Kxfs8VeIZUtDQggWlaQUCatQbsQIbAQULCQgHAgYVCgkI
+CwIEFgIDAQIeAQIXgAAKCRAZUtDQggWlafDmAP9iyDzytOMAqQej0R8sxDFAHiH6
+cE+c3gIvfHWghF6kYwD/bt2aiIarqijBsQROY5CyJH+remNqKOVOGyJsAlFe4gG4
+MwRq1BuxFgkrBgEEAdpHDwEBB0CE+mj+kgwnr8C2IODWr+ZK6LLNLO65HxoRyfwp
+8n6S04jvBBgWCAAgFiEEIVmK17c9SsX7PFXiGVLQ0IIFpWkFAmrUG7ECGwIAgQkQ
+GVLQ0IIFpWl2IAQZFggAHRYhBFievUOJK4rWAe10BrsgRY8YrabpBQJq1BuxAAoJ
+ELsgRY8YrabpErUBAOqdn7ASvsOjoCHZ2AFbbxy2rYUd+hv26voAaJHFK75dAP0a
+QdruyBOtzJdz2M5/Xed9EO9j70QWMHPn1UNhCUy+ACFSAP4lQkHU1jkc39uNO5H/
+eA4jBJmeQhxVt1XOf096TmAv+gEAnrLi2CyyLhEwU7RLJJaMOo9021BGFrmE1NFm
+VwZipww=
+=hc4A
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    static SUBKEY_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iHUEABYIAB0WIQRYnr1DiSuK1gHtdAa7IEWPGK2m6QUCatQbsQAKCRC7IEWPGK2m
+6ZaVAQC2mPNZqx35Htd880ZeBkJq2OIIybjDMOV4ZAdUgRgohAD/UNBnxk+1/7YN
+YQkQS6iOjQjMrFY79HLVre7NIf/cbQ4=
+=g7qS
+-----END PGP SIGNATURE-----
+";
+
+    // Made with `gpg --quick-gen-key ... ed25519 sign never`, then signed with
+    // `--sig-notation '!critical@example.com=yes'`, with the same notation not
+    // marked critical, and with `--ask-sig-expire --default-sig-expire 1d`.
+    static NOTATION_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatQhdhYJKwYBBAHaRw8BAQdArUwa7wpxOts11xbfU9N3yJbl204zztAxlc9Y
+yqMEKum0LUxhdW5jaGVyIFRlc3QgTm90YXRpb24gPG5vdGF0aW9uQGV4YW1wbGUu
+Y29tPoiQBBMWCAA4FiEEpENkSeSRsr0BNRt8sWumTr9iHa4FAmrUIXYCGwMFCwkI
+BwIGFQoJCAsCBBYCAwECHgECF4AACgkQsWumTr9iHa5XKgEAuPcQZzk5R2W375iN
+Jdq7Vyk/Y2WS8f9OV4WpOzA9huwA/0XW/6xEXly3shlTDSxoyBfpCnplQSibBr//
+F4BFeXkB
+=w9Xv
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    static CRITICAL_NOTATION_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iJYEABYIAD4WIQSkQ2RJ5JGyvQE1G3yxa6ZOv2IdrgUCatQhdiCUgAAAAAAUAANj
+cml0aWNhbEBleGFtcGxlLmNvbXllcwAKCRCxa6ZOv2IdruFoAQDlPa79VAjgJmJO
+ssDXn9cAHM2UTMktMbP61nd72RBpSgEA8nw9kfC1KuC8+kqbbcBoAGHFfem3tzT5
+3hVyJT+3LQA=
+=pvqs
+-----END PGP SIGNATURE-----
+";
+    static NOTATION_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iJMEABYIADsWIQSkQ2RJ5JGyvQE1G3yxa6ZOv2IdrgUCatQhdh0UgAAAAAARAANw
+bGFpbkBleGFtcGxlLmNvbXllcwAKCRCxa6ZOv2IdrjEMAP9KKzjVytTls6Wzc13A
+mo88XcepQoPCgnjiLDIvjiPOywEA4/IwhSPgx+Sf8zX8y/dHa45/IdGBeS9UecEs
+B8NMsQM=
+=9etc
+-----END PGP SIGNATURE-----
+";
+    static EXPIRING_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iHsEABYIACMWIQSkQ2RJ5JGyvQE1G3yxa6ZOv2IdrgUCatQhdgWDAAFRgAAKCRCx
+a6ZOv2IdronDAQDRRWtq9CgpB8jWY+3e6sbe6B/2yne8xjBHdhqSju1EAgD8CPpo
+krC9a483B29ZnnXue+3tXmgBGEHhGfdL2bznZwk=
+=8Dyc
+-----END PGP SIGNATURE-----
+";
+
+    // `SUBKEY_KEY` after `revkey` on its subkey in `gpg --edit-key`.
+    static REVOKED_SUBKEY_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatQbsRYJKwYBBAHaRw8BAQdAi0s08y04rNcLWLLzaIYXnzsMvd+lzB0cvkpR
+BJPkFdu0JkxhdW5jaGVyIFRlc3QgU3Via2V5IDxzdWJAZXhhbXBsZS5jb20+iJAE
+ExYIADgWIQQhWYrXtz1Kxfs8VeIZUtDQggWlaQUCatQbsQIbAQULCQgHAgYVCgkI
+CwIEFgIDAQIeAQIXgAAKCRAZUtDQggWlafDmAP9iyDzytOMAqQej0R8sxDFAHiH6
+cE+c3gIvfHWghF6kYwD/bt2aiIarqijBsQROY5CyJH+remNqKOVOGyJsAlFe4gG4
+MwRq1BuxFgkrBgEEAdpHDwEBB0CE+mj+kgwnr8C2IODWr+ZK6LLNLO65HxoRyfwp
+8n6S04h4BCgWCAAgFiEEIVmK17c9SsX7PFXiGVLQ0IIFpWkFAmrUG7cCHQAACgkQ
+GVLQ0IIFpWnnCQEAqgG5zzRyukp4s+3sbV5qzUESf86sE/XuF4DDeMDzGXgA/37F
+HZpmF54etZfoEIvillvYM0aCj15OvXjB/q3gDwIHiO8EGBYIACAWIQQhWYrXtz1K
+xfs8VeIZUtDQggWlaQUCatQbsQIbAgCBCRAZUtDQggWlaXYgBBkWCAAdFiEEWJ69
+Q4kritYB7XQGuyBFjxitpukFAmrUG7EACgkQuyBFjxitpukStQEA6p2fsBK+w6Og
+IdnYAVtvHLathR36G/bq+gBokcUrvl0A/RpB2u7IE63Ml3PYzn9d530Q72PvRBYw
+c+fVQ2EJTL4AIVIA/iVCQdTWORzf2407kf94DiMEmZ5CHFW3Vc5/T3pOYC/6AQCe
+suLYLLIuETBTtEsklow6j3TbUEYWuYTU0WZXBmKnDA==
+=Txtx
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+    // Made on 2020-01-01 with `gpg --faked-system-time` and an expiry of `1d`.
+    static EXPIRED_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEXgvhABYJKwYBBAHaRw8BAQdAwrhLwBlgZC3eALcZ4rIsk7eMgKoTgulisV2/
+8+VkZsO0K0xhdW5jaGVyIFRlc3QgRXhwaXJlZCA8ZXhwaXJlZEBleGFtcGxlLmNv
+bT6IlgQTFggAPhYhBF5ZdCGj7M/dR0fehZRiiSUuJdgxBQJeC+EAAhsDBQkAAVGA
+BQsJCAcCBhUKCQgLAgQWAgMBAh4BAheAAAoJEJRiiSUuJdgxpsQBAMNjl0RIwbBc
+Z17jtceIgU6eUugQwz3hXyvAHkx23OHzAQDirlkFO3jyuT/nlogJ6OHUc92mTkn8
+yoYDPaDOf3h/AQ==
+=Ji43
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    static EXPIRED_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iIoEABYIADIWIQReWXQho+zP3UdH3oWUYoklLiXYMQUCXgvvEBQcZXhwaXJlZEBl
+eGFtcGxlLmNvbQAKCRCUYoklLiXYMYasAQC1MydHSaeXHX2HuWU/MfTsZ/OhgPDV
+NVWGcdo4Kv0v7AEAo66thdg1fFDcMaRxsLxqFp65TKSugnDmDYZ0VYcKdQQ=
+=0zZq
+-----END PGP SIGNATURE-----
+";
+
+    // Made like `ED25519_KEY`, then revoked by importing its revocation
+    // certificate.
+    static REVOKED_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatQbsRYJKwYBBAHaRw8BAQdA6G7cD6kZyuJOzIDB/z1D6E4P1eNHCj9UeqMa
+q3MBVf+IeAQgFggAIBYhBB5Sx89As1VxwMtugRNDY6bxBpjaBQJq1BuxAh0AAAoJ
+EBNDY6bxBpjarzsA/iS5CNnVFahEZeVJcwdkR+NtRzK/dL+1pMy3Debfpu58AQCU
+kTcCbjHBUeziOGNCqRUV0LQAtA1YagNQdTjQOD4jC7QrTGF1bmNoZXIgVGVzdCBS
+ZXZva2VkIDxyZXZva2VkQGV4YW1wbGUuY29tPoiQBBMWCAA4FiEEHlLHz0CzVXHA
+y26BE0NjpvEGmNoFAmrUG7ECGwMFCwkIBwIGFQoJCAsCBBYCAwECHgECF4AACgkQ
+E0NjpvEGmNpIJAEA/AAL0KOI3OyRdgYntBPrX2Eox0UWrzcQHrxA4r7CQyEA/RfR
+9+faBw1T/o8DkxAlAMfCVXNIFkRZQg5KF7exfcII
+=Ad5L
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    static REVOKED_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iIoEABYIADIWIQQeUsfPQLNVccDLboETQ2Om8QaY2gUCatQbsRQccmV2b2tlZEBl
+eGFtcGxlLmNvbQAKCRATQ2Om8QaY2gb6AQC2I6k8brsRH2ty069N0/aWXKbls1ed
+Ko+an/oG58AvEQEAlAWb2XnlkXL0NLMESL6haykbdLTM1xS87AU1FeXCKQ4=
+=Qknq
+-----END PGP SIGNATURE-----
+";
+
+    #[test]
+    fn test_verify_rsa_signature() {
+        // Act
+        let valid = verify(
+            RSA_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            RSA_SIGNATURE.as_bytes(),
+        );
+        let tampered = verify(
+            RSA_KEY.as_bytes(),
+            CONTENT.replace("linux", "darwin").as_bytes(),
+            RSA_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid);
+        assert_eq!(tampered.err().as_deref(), Some("Signature does not match"));
+    }
+
+    #[test]
+    fn test_verify_ed25519_text_signature() {
+        // Act
+        let valid = verify(
+            ED25519_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            ED25519_SIGNATURE.as_bytes(),
+        );
+        let crlf_content = CONTENT.replace('\n', "\r\n");
+        let crlf = verify(
+            ED25519_KEY.as_bytes(),
+            crlf_content.as_bytes(),
+            ED25519_SIGNATURE.as_bytes(),
+        );
+        // Read in two chunks, split between a carriage return and its line
+        // feed.
+        let (first, second) = crlf_content
+            .as_bytes()
+            .split_at(crlf_content.find('\n').unwrap());
+        let chunked = verify(
+            ED25519_KEY.as_bytes(),
+            first.chain(second),
+            ED25519_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid);
+        assert!(crlf.is_ok(), "{:?}", crlf);
+        assert!(chunked.is_ok(), "{:?}", chunked);
+    }
+
+    #[test]
+    fn test_verify_with_binary_keyring() {
+        // Arrange
+        let keyring = [
+            dearmor(ED25519_KEY.as_bytes()).unwrap(),
+            dearmor(RSA_KEY.as_bytes()).unwrap(),
+        ]
+        .concat();
+
+        // Act
+        let rsa = verify(&keyring, CONTENT.as_bytes(), RSA_SIGNATURE.as_bytes());
+        let ed25519 = verify(&keyring, CONTENT.as_bytes(), ED25519_SIGNATURE.as_bytes());
+
+        // Assert
+        assert!(rsa.is_ok(), "{:?}", rsa);
+        assert!(ed25519.is_ok(), "{:?}", ed25519);
+    }
+
+    #[test]
+    fn test_verify_rejects_signature_of_other_key() {
+        // Act
+        let result = verify(
+            ED25519_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            RSA_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert_eq!(
+            result.err().as_deref(),
+            Some("Signed by key B6E50E557E11DC469CB84C254E5B0B54C104DC86, which is not trusted")
+        );
+        assert!(verify(b"", CONTENT.as_bytes(), RSA_SIGNATURE.as_bytes()).is_err());
+        assert!(verify(RSA_KEY.as_bytes(), CONTENT.as_bytes(), b"").is_err());
+    }
+
+    /// Packets with new format headers, so that keyrings can be spliced.
+    fn serialize(packets: &[Packet]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for packet in packets {
+            data.extend_from_slice(&[0xc0 | packet.tag, 0xff]);
+            data.extend_from_slice(&(packet.body.len() as u32).to_be_bytes());
+            data.extend_from_slice(packet.body);
+        }
+        data
+    }
+
+    #[test]
+    fn test_verify_signature_of_bound_subkey() {
+        // Arrange
+        let primary = dearmor(ED25519_KEY.as_bytes()).unwrap();
+        let subkey = dearmor(SUBKEY_KEY.as_bytes()).unwrap();
+        let subkey_packets = packets(&subkey).unwrap();
+        let start = subkey_packets
+            .iter()
+            .position(|packet| packet.tag == TAG_PUBLIC_SUBKEY)
+            .unwrap();
+        // The subkey and its binding signature, claimed by another key.
+        let transplanted = [primary, serialize(&subkey_packets[start..])].concat();
+
+        // Act
+        let valid = verify(
+            SUBKEY_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            SUBKEY_SIGNATURE.as_bytes(),
+        );
+        let unbound = verify(
+            &transplanted,
+            CONTENT.as_bytes(),
+            SUBKEY_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid);
+        assert_eq!(
+            unbound.err().as_deref(),
+            Some("Key 589EBD43892B8AD601ED7406BB20458F18ADA6E9 is not bound to its primary key")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_revoked_keys() {
+        // Act
+        let revoked = verify(
+            REVOKED_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            REVOKED_SIGNATURE.as_bytes(),
+        );
+        let revoked_subkey = verify(
+            REVOKED_SUBKEY_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            SUBKEY_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert_eq!(
+            revoked.err().as_deref(),
+            Some("Key 1E52C7CF40B35571C0CB6E81134363A6F10698DA has been revoked")
+        );
+        assert_eq!(
+            revoked_subkey.err().as_deref(),
+            Some("Key 589EBD43892B8AD601ED7406BB20458F18ADA6E9 has been revoked")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_expired_key() {
+        // Arrange
+        let signed_at = 1577840400;
+
+        // Act
+        let before_expiry = verify_at(
+            EXPIRED_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            EXPIRED_SIGNATURE.as_bytes(),
+            signed_at,
+        );
+        let expired = verify(
+            EXPIRED_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            EXPIRED_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert!(before_expiry.is_ok(), "{:?}", before_expiry);
+        assert_eq!(
+            expired.err().as_deref(),
+            Some("Key 5E597421A3ECCFDD4747DE85946289252E25D831 has expired")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_primary_key_not_allowed_to_sign() {
+        // Arrange
+        let keys = dearmor(SUBKEY_KEY.as_bytes()).unwrap();
+        let packets = packets(&keys).unwrap();
+        let end = packets
+            .iter()
+            .position(|packet| packet.tag == TAG_PUBLIC_SUBKEY)
+            .unwrap();
+        let primary = parse_certificates(&serialize(&packets[..end]), u32::MAX.into()).unwrap();
+
+        // Assert
+        assert_eq!(primary.len(), 1);
+        assert_eq!(
+            primary[0].unusable.as_deref(),
+            Some("Key 21598AD7B73D4AC5FB3C55E21952D0D08205A569 is not allowed to sign")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_critical_subpacket() {
+        // Act
+        let critical = verify(
+            NOTATION_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            CRITICAL_NOTATION_SIGNATURE.as_bytes(),
+        );
+        let not_critical = verify(
+            NOTATION_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            NOTATION_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert_eq!(
+            critical.err().as_deref(),
+            Some("Unsupported critical OpenPGP subpacket 20")
+        );
+        assert!(not_critical.is_ok(), "{:?}", not_critical);
+    }
+
+    #[test]
+    fn test_verify_rejects_expired_signature() {
+        // Arrange
+        let signed_at = 1792287094;
+
+        // Act
+        let before_expiry = verify_at(
+            NOTATION_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            EXPIRING_SIGNATURE.as_bytes(),
+            signed_at + 3600,
+        );
+        let expired = verify_at(
+            NOTATION_KEY.as_bytes(),
+            CONTENT.as_bytes(),
+            EXPIRING_SIGNATURE.as_bytes(),
+            signed_at + 2 * 86400,
+        );
+
+        // Assert
+        assert!(before_expiry.is_ok(), "{:?}", before_expiry);
+        assert_eq!(expired.err().as_deref(), Some("Signature has expired"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+static TERMINAL_INTERVAL: Duration = Duration::from_millis(100);
+static PLAIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reports download progress. On a terminal the status line is redrawn in
+/// place; otherwise a plain line is written every few seconds.
+pub struct Progress<W: Write> {
+    writer: W,
+    interactive: bool,
+    total: Option<u64>,
+    received: u64,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl Progress<std::io::Stderr> {
+    pub fn stderr(total: Option<u64>) -> Self {
+        let stderr = std::io::stderr();
+        let interactive = stderr.is_terminal();
+        Progress::new(stderr, interactive, total)
+    }
+}
+
+impl<W: Write> Progress<W> {
+    pub fn new(writer: W, interactive: bool, total: Option<u64>) -> Self {
+        let now = Instant::now();
+        Progress {
+            writer,
+            interactive,
+            total,
+            received: 0,
+            started: now,
+            last_report: now,
+        }
+    }
+
+    /// Records `bytes` more received, reporting if enough time has passed.
+    pub fn advance(&mut self, bytes: u64) {
+        self.received += bytes;
+        let interval = if self.interactive {
+            TERMINAL_INTERVAL
+        } else {
+            PLAIN_INTERVAL
+        };
+        if self.last_report.elapsed() >= interval {
+            self.report();
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.report();
+        if self.interactive {
+            let _ = writeln!(self.writer);
+        }
+    }
+
+    fn report(&mut self) {
+        self.last_report = Instant::now();
+        let line = render(self.received, self.total, self.started.elapsed());
+        // Progress output is best effort and must never fail the download.
+        let _ = if self.interactive {
+            write!(self.writer, "\r\x1b[K{}", line)
+        } else {
+            writeln!(self.writer, "{}", line)
+        };
+        let _ = self.writer.flush();
+    }
+}
+
+/// Formats a status line such as `1.5 MiB / 3.0 MiB (50%), 750.0 KiB/s, ETA 2s`.
+pub fn render(received: u64, total: Option<u64>, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 {
+        received as f64 / seconds
+    } else {
+        0.0
+    };
+
+    let mut line = format_bytes(received);
+    if let Some(total) = total.filter(|total| *total > 0) {
+        let percent = (received as f64 / total as f64 * 100.0).min(100.0);
+        line.push_str(&format!(" / {} ({:.0}%)", format_bytes(total), percent));
+    }
+    line.push_str(&format!(", {}/s", format_bytes(rate as u64)));
+    if let Some(total) = total
+        && rate > 0.0
+        && received < total
+    {
+        let remaining = Duration::from_secs_f64((total - received) as f64 / rate);
+        line.push_str(&format!(", ETA {}", format_duration(remaining)));
+    }
+    line
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with_known_total() {
+        let line = render(1536 * 1024, Some(3 * 1024 * 1024), Duration::from_secs(2));
+
+        assert_eq!(line, "1.5 MiB / 3.0 MiB (50%), 768.0 KiB/s, ETA 2s");
+    }
+
+    #[test]
+    fn test_render_without_total() {
+        let line = render(512, None, Duration::from_secs(1));
+
+        assert_eq!(line, "512 B, 512 B/s");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+        assert_eq!(format_duration(Duration::from_secs(7260)), "2h01m");
+    }
+
+    #[test]
+    fn test_plain_output_writes_lines() {
+        // Arrange
+        let mut output = Vec::new();
+        {
+            let mut progress = Progress::new(&mut output, false, Some(2048));
+
+            // Act
+            progress.advance(1024);
+            progress.advance(1024);
+            progress.finish();
+        }
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains('\r'));
+        assert!(output.starts_with("2.0 KiB / 2.0 KiB (100%)"));
+        assert!(output.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_terminal_output_redraws_in_place() {
+        // Arrange
+        let mut output = Vec::new();
+        {
+            let mut progress = Progress::new(&mut output, true, None);
+
+            // Act
+            progress.advance(100);
+            progress.finish();
+        }
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("\r\x1b[K100 B"));
+        assert!(output.ends_with('\n'));
+    }
+}
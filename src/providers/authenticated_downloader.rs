@@ -0,0 +1,366 @@
+use crate::credentials::CredentialSources;
+use crate::digest::{Algorithm, Digest};
+use crate::models::configuration::File;
+use crate::models::credentials::Credential;
+use crate::providers::http_download;
+use crate::signature;
+use std::path::Path;
+
+/// Downloads with a credential: a bearer token, basic authentication and any
+/// custom headers it defines.
+pub struct AuthenticatedDownloader {
+    client: reqwest::Client,
+    /// Resolved from the environment for each download when not given.
+    credential: Option<Credential>,
+}
+
+impl AuthenticatedDownloader {
+    pub fn with_credential(credential: Credential) -> Self {
+        AuthenticatedDownloader {
+            client: http_download::client(),
+            credential: Some(credential),
+        }
+    }
+
+    async fn credential(&self, configuration: &File, url: &str) -> Result<Credential, String> {
+        match &self.credential {
+            Some(credential) => Ok(credential.clone()),
+            None => CredentialSources::from_environment()
+                .resolve(configuration.credential.as_deref(), url)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("No credential found for {}", url)),
+        }
+    }
+}
+
+impl crate::providers::downloader::Downloader for AuthenticatedDownloader {
+    fn new() -> impl super::downloader::Downloader {
+        AuthenticatedDownloader {
+            client: http_download::client(),
+            credential: None,
+        }
+    }
+
+    async fn build_url_and_digest(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+    ) -> Result<(String, Digest), Box<dyn std::error::Error>> {
+        let url = http_download::build_url(configuration, os, arch)?;
+        let credential = self.credential(configuration, &url).await?;
+        let digest = http_download::expected_digest(
+            &self.client,
+            configuration,
+            os,
+            arch,
+            &url,
+            &http_download::file_name(&url),
+            &|request| authorize(request, &credential),
+        )
+        .await?;
+        Ok((url, digest))
+    }
+
+    async fn download_and_validate_digest(
+        &self,
+        configuration: &File,
+        url: &str,
+        digest: &Digest,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let credential = self.credential(configuration, url).await?;
+
+        http_download::download_and_validate_digest(
+            &self.client,
+            configuration,
+            url,
+            digest,
+            destination,
+            &|request| authorize(request, &credential),
+        )
+        .await
+    }
+
+    async fn verify_signature(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+        url: &str,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(signature) = &configuration.signature else {
+            return Ok(());
+        };
+        let signature_url = signature::url(signature, &configuration.version, os, arch, url);
+        let credential = self.credential(configuration, url).await?;
+
+        http_download::verify_signature(
+            &self.client,
+            signature,
+            url,
+            &signature_url,
+            destination,
+            &|request| authorize(request, &credential),
+        )
+        .await
+    }
+
+    async fn fetch_digests(
+        &self,
+        configuration: &File,
+        url: &str,
+        algorithms: &[Algorithm],
+    ) -> Result<Vec<Digest>, Box<dyn std::error::Error>> {
+        let credential = self.credential(configuration, url).await?;
+        Ok(
+            http_download::fetch_digests(&self.client, url, algorithms, &|request| {
+                authorize(request, &credential)
+            })
+            .await?,
+        )
+    }
+}
+
+/// Adds `credential` to `request`: a bearer token or basic authentication,
+/// and its custom headers.
+pub fn authorize(
+    mut request: reqwest::RequestBuilder,
+    credential: &Credential,
+) -> reqwest::RequestBuilder {
+    if let Some(token) = &credential.token {
+        request = request.bearer_auth(token);
+    } else if let Some(username) = &credential.username {
+        request = request.basic_auth(username, credential.password.as_ref());
+    }
+    for (name, value) in &credential.headers {
+        request = request.header(name, value);
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::downloader::Downloader;
+
+    use super::*;
+    use crate::providers::http_download::test_support::{get_available_port, sha256_of};
+    use iron::{Iron, IronResult, Listening, Request, Response, status};
+
+    use std::sync::{Arc, Mutex};
+
+    /// Answers every request with a redirect to `location`.
+    fn redirecting_server(location: String) -> (Listening, u16) {
+        let handler = move |_: &mut Request| -> IronResult<Response> {
+            let mut response = Response::with(status::Found);
+            response
+                .headers
+                .set_raw("Location", vec![location.clone().into_bytes()]);
+            Ok(response)
+        };
+        let port = get_available_port().expect("No available port found");
+        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+    }
+
+    /// Serves `content` and records the value of the `header` it received,
+    /// after redirecting requests for `/old` to `/`.
+    fn recording_server(
+        content: &'static [u8],
+        header: &'static str,
+    ) -> (Listening, u16, Arc<Mutex<Option<String>>>) {
+        let received = Arc::new(Mutex::new(None));
+        let recorded = received.clone();
+        let handler = move |req: &mut Request| -> IronResult<Response> {
+            if req.url.path() == ["old"] {
+                let mut response = Response::with(status::MovedPermanently);
+                response.headers.set_raw("Location", vec![b"/".to_vec()]);
+                return Ok(response);
+            }
+            *recorded.lock().unwrap() = req
+                .headers
+                .get_raw(header)
+                .map(|values| String::from_utf8_lossy(&values[0]).to_string());
+            Ok(Response::with((status::Ok, content)))
+        };
+        let port = get_available_port().expect("No available port found");
+
+        (
+            Iron::new(handler).http(("127.0.0.1", port)).unwrap(),
+            port,
+            received,
+        )
+    }
+
+    fn configuration() -> File {
+        serde_yaml::from_str(
+            r#"
+name: test
+version: 1.0.0
+urlPattern: http://127.0.0.1/test
+platforms: {}
+retry:
+    retries: 0
+"#,
+        )
+        .unwrap()
+    }
+
+    async fn download_with(credential: Credential, header: &'static str) -> Option<String> {
+        let content = b"test content";
+        let (mut server, port, received) = recording_server(content, header);
+        let url = format!("http://127.0.0.1:{}", port);
+        download_from(&url, credential, content).await;
+
+        server.close().unwrap();
+        received.lock().unwrap().clone()
+    }
+
+    async fn download_from(url: &str, credential: Credential, content: &[u8]) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        AuthenticatedDownloader::with_credential(credential)
+            .download_and_validate_digest(&configuration(), url, &sha256_of(content), &destination)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_sends_bearer_token() {
+        // Arrange
+        let credential = Credential {
+            token: Some("secret".to_string()),
+            ..Credential::default()
+        };
+
+        // Act
+        let authorization = download_with(credential, "Authorization").await;
+
+        // Assert
+        assert_eq!(authorization.as_deref(), Some("Bearer secret"));
+    }
+
+    #[tokio::test]
+    async fn test_download_sends_basic_auth() {
+        // Arrange
+        let credential = Credential {
+            username: Some("me".to_string()),
+            password: Some("hunter2".to_string()),
+            ..Credential::default()
+        };
+
+        // Act
+        let authorization = download_with(credential, "Authorization").await;
+
+        // Assert
+        assert_eq!(authorization.as_deref(), Some("Basic bWU6aHVudGVyMg=="));
+    }
+
+    #[tokio::test]
+    async fn test_download_sends_custom_headers() {
+        // Arrange
+        let credential = Credential {
+            headers: [("X-JFrog-Art-Api".to_string(), "key".to_string())].into(),
+            ..Credential::default()
+        };
+
+        // Act
+        let api_key = download_with(credential, "X-JFrog-Art-Api").await;
+
+        // Assert
+        assert_eq!(api_key.as_deref(), Some("key"));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_another_host_drops_credential() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, received) = recording_server(content, "X-JFrog-Art-Api");
+        let (mut port_redirect, port_redirect_port) =
+            redirecting_server(format!("http://127.0.0.1:{}/", port));
+        let (mut host_redirect, host_redirect_port) =
+            redirecting_server(format!("http://localhost:{}/", port));
+        let credential = Credential {
+            headers: [("X-JFrog-Art-Api".to_string(), "key".to_string())].into(),
+            ..Credential::default()
+        };
+
+        // Act
+        let url = format!("http://127.0.0.1:{}/tool", host_redirect_port);
+        download_from(&url, credential.clone(), content).await;
+        let redirected = received.lock().unwrap().take();
+        let url = format!("http://127.0.0.1:{}/tool", port_redirect_port);
+        download_from(&url, credential, content).await;
+        let redirected_to_other_port = received.lock().unwrap().take();
+
+        // Assert
+        assert_eq!(redirected, None);
+        assert_eq!(redirected_to_other_port, None);
+
+        // Clean up
+        server.close().unwrap();
+        port_redirect.close().unwrap();
+        host_redirect.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_redirect_within_origin_keeps_credential() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, received) = recording_server(content, "X-JFrog-Art-Api");
+        let credential = Credential {
+            headers: [("X-JFrog-Art-Api".to_string(), "key".to_string())].into(),
+            ..Credential::default()
+        };
+
+        // Act
+        let url = format!("http://127.0.0.1:{}/old", port);
+        download_from(&url, credential, content).await;
+
+        // Assert
+        assert_eq!(received.lock().unwrap().as_deref(), Some("key"));
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checksums_on_another_port_are_fetched_without_credential() {
+        // Arrange
+        static CHECKSUMS: &[u8] =
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa  tool\n";
+        let (mut server, port, received) = recording_server(CHECKSUMS, "X-JFrog-Art-Api");
+        let other_port = get_available_port().expect("No available port found");
+        let credential = Credential {
+            headers: [("X-JFrog-Art-Api".to_string(), "key".to_string())].into(),
+            ..Credential::default()
+        };
+        let digest_with_artifact_on = |artifact_port: u16| {
+            let mut configuration = configuration();
+            configuration.url_pattern = format!("http://127.0.0.1:{}/tool", artifact_port);
+            configuration.checksums_url = Some(format!("http://127.0.0.1:{}/SHA256SUMS", port));
+            let downloader = AuthenticatedDownloader::with_credential(credential.clone());
+            async move {
+                downloader
+                    .build_url_and_digest(&configuration, "linux", "amd64")
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Act
+        digest_with_artifact_on(other_port).await;
+        let other_origin = received.lock().unwrap().take();
+        digest_with_artifact_on(port).await;
+        let same_origin = received.lock().unwrap().take();
+
+        // Assert
+        assert_eq!(other_origin, None);
+        assert_eq!(same_origin.as_deref(), Some("key"));
+
+        // Clean up
+        server.close().unwrap();
+    }
+}
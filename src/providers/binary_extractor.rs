@@ -0,0 +1,60 @@
+use crate::providers::extractor::make_executable;
+
+/// Handles downloads that are the executable itself rather than an archive.
+pub struct BinaryExtractor {}
+
+impl crate::providers::extractor::Extractor for BinaryExtractor {
+    fn new() -> impl crate::providers::extractor::Extractor {
+        BinaryExtractor {}
+    }
+
+    async fn extract_to_disk(
+        &self,
+        archive_path: &std::path::Path,
+        folder: &std::path::Path,
+        executable_path: &std::path::Path,
+        _strip_prefix: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let parent = executable_path.parent().unwrap_or(folder);
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create dir {}, {}", parent.display(), e))?;
+        tokio::fs::copy(archive_path, executable_path)
+            .await
+            .map_err(|e| format!("Failed to write file {}, {}", executable_path.display(), e))?;
+        make_executable(executable_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::extractor::Extractor;
+
+    use super::*;
+    use crate::providers::extractor::test_support::write_archive;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_extract_to_disk_writes_executable() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().join("downloaded");
+        let executable_path = folder.join("jq");
+        let content = b"\x7fELF binary content".to_vec();
+        let archive_path = write_archive(temp_dir.path(), &content);
+
+        // Act
+        let extractor = BinaryExtractor::new();
+        extractor
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(fs::read(&executable_path).unwrap(), content);
+        let mode = fs::metadata(&executable_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+}
@@ -1,20 +1,50 @@
-use bytes::Bytes;
+use std::path::Path;
 
+use crate::digest::{Algorithm, Digest};
 use crate::models::configuration::File;
 
 pub trait Downloader {
     fn new() -> impl Downloader;
 
-    fn build_url_and_sha256(
+    /// The URL of the artifact and its expected digest, looked up in the
+    /// `checksumsUrl` file when there is one, otherwise taken from `platforms`.
+    fn build_url_and_digest(
         &self,
         configuration: &File,
         os : &str,
         arch: &str,
-    ) -> impl std::future::Future<Output = Result<(String, String), Box<dyn std::error::Error>>> + Send;
+    ) -> impl std::future::Future<Output = Result<(String, Digest), Box<dyn std::error::Error>>> + Send;
 
-    fn download_and_validate_sha256(
+    /// Streams `url` into `destination`, hashing as it goes. Transient
+    /// failures are retried as configured; the file is removed again when the
+    /// digest does not match.
+    fn download_and_validate_digest(
         &self,
+        configuration: &File,
+        url: &str,
+        digest: &Digest,
+        destination: &Path,
+    ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send;
+
+    /// Checks the downloaded artifact in `destination` against the
+    /// `signature` of the configuration, if any. The file is removed again
+    /// when it does not verify.
+    fn verify_signature(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+        url: &str,
+        destination: &Path,
+    ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send;
+
+    /// Hashes the artifact at `url` with each of `algorithms` in a single
+    /// download, without keeping it, to write its digests into the
+    /// configuration.
+    fn fetch_digests(
+        &self,
+        configuration: &File,
         url: &str,
-        sha256: &str,
-    ) -> impl std::future::Future<Output = Result<Bytes, Box<dyn std::error::Error>>> + Send;
+        algorithms: &[Algorithm],
+    ) -> impl std::future::Future<Output = Result<Vec<Digest>, Box<dyn std::error::Error>>> + Send;
 }
\ No newline at end of file
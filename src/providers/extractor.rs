@@ -1,14 +1,132 @@
-use bytes::Bytes;
-
-use std::path::PathBuf;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
 
 pub trait Extractor {
     fn new() -> impl Extractor;
 
     fn extract_to_disk(
         &self,
-        content: &Bytes,
-        folder: &PathBuf,
-        executable_path: &PathBuf,
+        archive_path: &Path,
+        folder: &Path,
+        executable_path: &Path,
+        strip_prefix: Option<&str>,
     ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send;
 }
+
+/// Maps an archive entry to its path relative to the extraction folder, with
+/// `strip_prefix` removed from the front. Returns `None` for entries outside
+/// the prefix and for the prefix directory itself.
+pub fn entry_destination(
+    entry_path: &Path,
+    strip_prefix: Option<&str>,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let normalized: PathBuf = entry_path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    let relative = match strip_prefix {
+        Some(prefix) => match normalized.strip_prefix(prefix) {
+            Ok(relative) => relative,
+            Err(_) => return Ok(None),
+        },
+        None => normalized.as_path(),
+    };
+    if relative.as_os_str().is_empty() {
+        return Ok(None);
+    }
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "Refusing to extract {} outside of the target folder",
+            entry_path.display()
+        )
+        .into());
+    }
+    Ok(Some(relative.to_path_buf()))
+}
+
+pub fn open_archive(
+    archive_path: &Path,
+) -> Result<std::io::BufReader<std::fs::File>, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open archive {}, {}", archive_path.display(), e))?;
+    Ok(std::io::BufReader::new(file))
+}
+
+pub async fn make_executable(executable_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut permissions = tokio::fs::metadata(executable_path)
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to read metadata {}, {}",
+                executable_path.display(),
+                e
+            )
+        })?
+        .permissions();
+    permissions.set_mode(0o755);
+    tokio::fs::set_permissions(executable_path, permissions)
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to set permissions {}, {}",
+                executable_path.display(),
+                e
+            )
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use std::path::{Path, PathBuf};
+
+    /// Writes downloaded `content` next to the extraction folder.
+    pub fn write_archive(dir: &Path, content: &[u8]) -> PathBuf {
+        let archive_path = dir.join("download");
+        std::fs::write(&archive_path, content).unwrap();
+        archive_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_destination_strips_prefix() {
+        let destination = entry_destination(
+            Path::new("./node-v20.0.0-linux-x64/bin/node"),
+            Some("node-v20.0.0-linux-x64/"),
+        )
+        .unwrap();
+        assert_eq!(destination, Some(PathBuf::from("bin/node")));
+    }
+
+    #[test]
+    fn test_entry_destination_skips_prefix_directory_and_outsiders() {
+        let prefix = Some("node-v20.0.0-linux-x64");
+        assert_eq!(
+            entry_destination(Path::new("node-v20.0.0-linux-x64/"), prefix).unwrap(),
+            None
+        );
+        assert_eq!(
+            entry_destination(Path::new("pax_global_header"), prefix).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entry_destination_without_prefix() {
+        let destination = entry_destination(Path::new("bin/helm"), None).unwrap();
+        assert_eq!(destination, Some(PathBuf::from("bin/helm")));
+    }
+
+    #[test]
+    fn test_entry_destination_rejects_parent_components() {
+        assert!(entry_destination(Path::new("../escape"), None).is_err());
+        assert!(entry_destination(Path::new("/etc/passwd"), None).is_err());
+    }
+}
@@ -67,6 +67,7 @@ impl MarkerManager for FileMarkerManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::digest::{Algorithm, Digest};
     use crate::models::download_marker::MarkerFile;
     use tokio::fs;
     use tempfile::tempdir;
@@ -77,7 +78,7 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let marker_path = temp_dir.path().join("marker.yaml");
         let marker_file = MarkerFile {
-            sha256: "dummysha256".to_string(),
+            digest: Digest::new(Algorithm::Sha256, &"a".repeat(64)).unwrap(),
             url: "https://example.com/test.zip".to_string(),
         };
         let manager = FileMarkerManager {};
@@ -111,7 +112,7 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let marker_path = temp_dir.path().join("marker.yaml");
         let marker_file = MarkerFile {
-            sha256: "dummysha256".to_string(),
+            digest: Digest::new(Algorithm::Sha256, &"a".repeat(64)).unwrap(),
             url: "https://example.com/test.zip".to_string(),
         };
         let yaml_content = serde_yaml::to_string(&marker_file).unwrap();
@@ -143,14 +144,14 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let marker_path = temp_dir.path().join("marker.yaml");
         let old_marker_file = MarkerFile {
-            sha256: "oldsha256".to_string(),
+            digest: Digest::new(Algorithm::Sha256, &"0".repeat(64)).unwrap(),
             url: "https://example.com/old.zip".to_string(),
         };
         let old_yaml_content = serde_yaml::to_string(&old_marker_file).unwrap();
         fs::write(&marker_path, old_yaml_content).await.unwrap();
 
         let new_marker_file = MarkerFile {
-            sha256: "newsha256".to_string(),
+            digest: Digest::new(Algorithm::Sha256, &"1".repeat(64)).unwrap(),
             url: "https://example.com/new.zip".to_string(),
         };
 
@@ -0,0 +1,392 @@
+use crate::digest::{Algorithm, Digest};
+use crate::github;
+use crate::models::configuration::File;
+use crate::models::credentials::Credential;
+use crate::models::github::Release;
+use crate::providers::authenticated_downloader::authorize;
+use crate::providers::http_download;
+use crate::signature;
+use std::path::Path;
+
+/// Downloads an asset of a GitHub release, resolved through the REST API.
+pub struct GithubDownloader {
+    client: reqwest::Client,
+    credential: Option<Credential>,
+}
+
+impl GithubDownloader {
+    pub fn with_credential(credential: Option<Credential>) -> Self {
+        GithubDownloader {
+            client: http_download::client(),
+            credential,
+        }
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.credential {
+            Some(credential) => authorize(request, credential),
+            None => request,
+        }
+    }
+
+    async fn fetch_release(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+    ) -> Result<Release, Box<dyn std::error::Error>> {
+        let repo = github::repo(configuration)?;
+        let tags = github::tag_candidates(configuration, os, arch);
+        for tag in &tags {
+            let url = format!(
+                "{}/repos/{}/releases/tags/{}",
+                github::api_url(configuration),
+                repo,
+                tag
+            );
+            let response = http_download::get(
+                &self.client,
+                &url,
+                &|request| request.header(reqwest::header::ACCEPT, "application/vnd.github+json"),
+                &|request| self.authorize(request),
+            )
+            .await
+            .map_err(|e| format!("Failed to query {}, {}", url, e))?;
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            let body = response
+                .text()
+                .await
+                .map_err(|e| format!("Failed to query {}, {}", url, e))?;
+            if !status.is_success() {
+                return Err(format!(
+                    "Failed to query {}, server returned {}: {}",
+                    url,
+                    status,
+                    http_download::body_snippet(&body)
+                )
+                .into());
+            }
+            return serde_json::from_str(&body)
+                .map_err(|e| format!("Failed to parse release from {}, {}", url, e).into());
+        }
+
+        Err(format!("No release of {} tagged {}", repo, tags.join(" or ")).into())
+    }
+}
+
+impl crate::providers::downloader::Downloader for GithubDownloader {
+    /// Anonymous, as the API host a token may be sent to depends on the
+    /// configuration; see `github::credential`.
+    fn new() -> impl super::downloader::Downloader {
+        GithubDownloader::with_credential(None)
+    }
+
+    async fn build_url_and_digest(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+    ) -> Result<(String, Digest), Box<dyn std::error::Error>> {
+        let pattern = configuration
+            .asset
+            .as_deref()
+            .ok_or("The github source requires asset")?;
+        let pattern = crate::template::render(pattern, &configuration.version, os, arch);
+        let release = self.fetch_release(configuration, os, arch).await?;
+        let asset = github::select_asset(&release, &pattern)?;
+        let digest = http_download::expected_digest(
+            &self.client,
+            configuration,
+            os,
+            arch,
+            &asset.url,
+            &asset.name,
+            &|request| self.authorize(request),
+        )
+        .await?;
+        Ok((asset.url.clone(), digest))
+    }
+
+    async fn download_and_validate_digest(
+        &self,
+        configuration: &File,
+        url: &str,
+        digest: &Digest,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        http_download::download_and_validate_digest(
+            &self.client,
+            configuration,
+            url,
+            digest,
+            destination,
+            &|request| {
+                self.authorize(request)
+                    .header(reqwest::header::ACCEPT, "application/octet-stream")
+            },
+        )
+        .await
+    }
+
+    async fn verify_signature(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+        url: &str,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(signature) = &configuration.signature else {
+            return Ok(());
+        };
+        let signature_url = match &signature.url {
+            Some(_) => signature::url(signature, &configuration.version, os, arch, url),
+            None => {
+                // Published as another asset of the release, named after the
+                // artifact.
+                let release = self.fetch_release(configuration, os, arch).await?;
+                let asset = release
+                    .assets
+                    .iter()
+                    .find(|asset| asset.url == url)
+                    .ok_or_else(|| format!("No asset at {} in the release", url))?;
+                let name = format!("{}{}", asset.name, signature::extension(signature.format));
+                release
+                    .assets
+                    .iter()
+                    .find(|asset| asset.name == name)
+                    .map(|asset| asset.url.clone())
+                    .ok_or_else(|| format!("No signature {} in the release", name))?
+            }
+        };
+        http_download::verify_signature(
+            &self.client,
+            signature,
+            url,
+            &signature_url,
+            destination,
+            &|request| {
+                self.authorize(request)
+                    .header(reqwest::header::ACCEPT, "application/octet-stream")
+            },
+        )
+        .await
+    }
+
+    async fn fetch_digests(
+        &self,
+        _configuration: &File,
+        url: &str,
+        algorithms: &[Algorithm],
+    ) -> Result<Vec<Digest>, Box<dyn std::error::Error>> {
+        Ok(
+            http_download::fetch_digests(&self.client, url, algorithms, &|request| {
+                self.authorize(request)
+                    .header(reqwest::header::ACCEPT, "application/octet-stream")
+            })
+            .await?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::downloader::Downloader;
+
+    use super::*;
+    use crate::digest::Algorithm;
+    use crate::providers::http_download::test_support::{digest_of, get_available_port};
+    use iron::{Iron, IronResult, Listening, Request, Response, status};
+
+    use std::sync::{Arc, Mutex};
+
+    static CONTENT: &[u8] = b"test content";
+
+    /// Path, `Accept` and `Authorization` header of each request received.
+    type Requests = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// A stand-in for the GitHub API serving release `1.0.0` of
+    /// `owner/tool`, tagged `tag`, recording the requests it receives.
+    fn github_server(tag: &'static str) -> (Listening, u16, Requests) {
+        let port = get_available_port().expect("No available port found");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let handler = move |req: &mut Request| -> IronResult<Response> {
+            let header = |name: &str| {
+                req.headers
+                    .get_raw(name)
+                    .map(|values| String::from_utf8_lossy(&values[0]).to_string())
+                    .unwrap_or_default()
+            };
+            let path = format!("/{}", req.url.path().join("/"));
+            recorded.lock().unwrap().push((
+                path.clone(),
+                header("Accept"),
+                header("Authorization"),
+            ));
+
+            if path == format!("/repos/owner/tool/releases/tags/{}", tag) {
+                let release = format!(
+                    r#"{{"tag_name": "{tag}", "assets": [
+                        {{"name": "tool_linux_amd64.tar.gz", "url": "http://127.0.0.1:{port}/assets/1", "browser_download_url": "http://127.0.0.1:{port}/download/1"}},
+                        {{"name": "tool_darwin_arm64.tar.gz", "url": "http://127.0.0.1:{port}/assets/2", "browser_download_url": "http://127.0.0.1:{port}/download/2"}}
+                    ]}}"#
+                );
+                return Ok(Response::with((status::Ok, release)));
+            }
+            if path == "/assets/1" {
+                return Ok(Response::with((status::Ok, CONTENT)));
+            }
+            Ok(Response::with((
+                status::NotFound,
+                r#"{"message": "Not Found"}"#,
+            )))
+        };
+
+        (
+            Iron::new(handler).http(("127.0.0.1", port)).unwrap(),
+            port,
+            requests,
+        )
+    }
+
+    fn configuration(port: u16) -> File {
+        serde_yaml::from_str(&format!(
+            r#"
+name: tool
+version: 1.0.0
+source: github
+repo: owner/tool
+asset: tool_{{{{os}}}}_{{{{arch}}}}.tar.gz
+apiUrl: http://127.0.0.1:{}
+platforms:
+    linux:
+        amd64:
+            blake3: {}
+retry:
+    retries: 0
+"#,
+            port,
+            digest_of(Algorithm::Blake3, CONTENT).hex
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_url_resolves_asset_of_release() {
+        // Arrange
+        let (mut server, port, _) = github_server("v1.0.0");
+        let downloader = GithubDownloader::with_credential(None);
+
+        // Act
+        let (url, _) = downloader
+            .build_url_and_digest(&configuration(port), "linux", "amd64")
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(url, format!("http://127.0.0.1:{}/assets/1", port));
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_build_url_falls_back_to_tag_without_v() {
+        // Arrange
+        let (mut server, port, requests) = github_server("1.0.0");
+        let downloader = GithubDownloader::with_credential(None);
+
+        // Act
+        let result = downloader
+            .build_url_and_digest(&configuration(port), "linux", "amd64")
+            .await;
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        let paths: Vec<String> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.0.clone())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/repos/owner/tool/releases/tags/v1.0.0".to_string(),
+                "/repos/owner/tool/releases/tags/1.0.0".to_string(),
+            ]
+        );
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_build_url_without_release_fails() {
+        // Arrange
+        let (mut server, port, _) = github_server("v2.0.0");
+        let downloader = GithubDownloader::with_credential(None);
+
+        // Act
+        let result = downloader
+            .build_url_and_digest(&configuration(port), "linux", "amd64")
+            .await;
+
+        // Assert
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("No release of owner/tool"), "{}", error);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_asset_with_token() {
+        // Arrange
+        let (mut server, port, requests) = github_server("v1.0.0");
+        let downloader = GithubDownloader::with_credential(Some(Credential {
+            token: Some("secret".to_string()),
+            ..Credential::default()
+        }));
+        let configuration = configuration(port);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let (url, digest) = downloader
+            .build_url_and_digest(&configuration, "linux", "amd64")
+            .await
+            .unwrap();
+        let result = downloader
+            .download_and_validate_digest(&configuration, &url, &digest, &destination)
+            .await;
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert_eq!(tokio::fs::read(&destination).await.unwrap(), CONTENT);
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0],
+            (
+                "/repos/owner/tool/releases/tags/v1.0.0".to_string(),
+                "application/vnd.github+json".to_string(),
+                "Bearer secret".to_string()
+            )
+        );
+        assert_eq!(
+            requests[1],
+            (
+                "/assets/1".to_string(),
+                "application/octet-stream".to_string(),
+                "Bearer secret".to_string()
+            )
+        );
+
+        // Clean up
+        server.close().unwrap();
+    }
+}
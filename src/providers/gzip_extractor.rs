@@ -0,0 +1,93 @@
+use crate::providers::extractor::{make_executable, open_archive};
+
+/// Handles a single gzip-compressed executable such as `tool_linux_amd64.gz`.
+pub struct GzipExtractor {}
+
+impl crate::providers::extractor::Extractor for GzipExtractor {
+    fn new() -> impl crate::providers::extractor::Extractor {
+        GzipExtractor {}
+    }
+
+    async fn extract_to_disk(
+        &self,
+        archive_path: &std::path::Path,
+        folder: &std::path::Path,
+        executable_path: &std::path::Path,
+        _strip_prefix: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let parent = executable_path.parent().unwrap_or(folder);
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create dir {}, {}", parent.display(), e))?;
+
+        let mut decoder = flate2::read::GzDecoder::new(open_archive(archive_path)?);
+        let mut outfile = std::fs::File::create(executable_path)
+            .map_err(|e| format!("Failed to create file {}, {}", executable_path.display(), e))?;
+        if let Err(e) = std::io::copy(&mut decoder, &mut outfile) {
+            drop(outfile);
+            tokio::fs::remove_file(executable_path).await?;
+            return Err(format!("Failed to decompress gzip stream: {}", e).into());
+        }
+        make_executable(executable_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::extractor::Extractor;
+
+    use super::*;
+    use crate::providers::extractor::test_support::write_archive;
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_extract_to_disk_decompresses_executable() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().join("downloaded");
+        let executable_path = folder.join("tool");
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"Executable content").unwrap();
+        let content = encoder.finish().unwrap();
+        let archive_path = write_archive(temp_dir.path(), &content);
+
+        // Act
+        let extractor = GzipExtractor::new();
+        extractor
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            fs::read_to_string(&executable_path).unwrap(),
+            "Executable content"
+        );
+        let mode = fs::metadata(&executable_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_extract_to_disk_fails_on_invalid_stream() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().to_path_buf();
+        let executable_path = folder.join("tool");
+        let content = b"not gzip".to_vec();
+        let archive_path = write_archive(temp_dir.path(), &content);
+
+        // Act
+        let extractor = GzipExtractor::new();
+        let result = extractor
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert!(!executable_path.exists());
+    }
+}
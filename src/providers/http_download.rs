@@ -0,0 +1,624 @@
+use crate::digest::{Algorithm, Digest, Hasher};
+use crate::models::configuration::{File, Signature};
+use crate::models::partial_download::PartialDownload;
+use crate::progress::Progress;
+use crate::{checksums, retry, signature, template};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Adds whatever a downloader needs to a request, such as credentials.
+pub type Authorize<'a> = dyn Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Sync + 'a;
+
+/// As many redirects as browsers and the default policy of reqwest follow.
+const MAX_REDIRECTS: usize = 10;
+
+/// Why a single download attempt failed.
+enum Failure {
+    /// Worth another attempt, after the delay the server asked for if any.
+    Retryable(String, Option<Duration>),
+    Fatal(String),
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_builder() {
+            Failure::Fatal(error.to_string())
+        } else {
+            Failure::Retryable(error.to_string(), None)
+        }
+    }
+}
+
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .read_timeout(Duration::from_secs(60))
+        .user_agent(concat!("launcher/", env!("CARGO_PKG_VERSION")))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_default()
+}
+
+/// Sends a GET of `url` with `prepare` applied to every request, following
+/// redirects here rather than in the client: `authorize` is only applied to
+/// requests to the origin of `url`, as the client would carry custom headers
+/// such as `X-JFrog-Art-Api` along to any host.
+pub async fn get(
+    client: &reqwest::Client,
+    url: &str,
+    prepare: &Authorize<'_>,
+    authorize: &Authorize<'_>,
+) -> reqwest::Result<reqwest::Response> {
+    let mut target = url.to_string();
+    let mut redirects = 0;
+    loop {
+        let mut request = prepare(client.get(&target));
+        if same_origin(url, &target) {
+            request = authorize(request);
+        }
+        let response = request.send().await?;
+        if !response.status().is_redirection() || redirects == MAX_REDIRECTS {
+            return Ok(response);
+        }
+        let Some(next) = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok())
+        else {
+            return Ok(response);
+        };
+        target = next.to_string();
+        redirects += 1;
+    }
+}
+
+/// The download URL of the `url` source.
+pub fn build_url(
+    configuration: &File,
+    os: &str,
+    arch: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if configuration.url_pattern.is_empty() {
+        return Err("The url source requires urlPattern".into());
+    }
+    Ok(template::render(
+        &configuration.url_pattern,
+        &configuration.version,
+        os,
+        arch,
+    ))
+}
+
+/// The last path segment of `url`, which is how checksums files name it.
+pub fn file_name(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back().map(str::to_string))
+        })
+        .unwrap_or_default()
+}
+
+/// The digest that the artifact at `url`, named `file_name`, must have:
+/// looked up in the checksums file when the configuration has one, otherwise
+/// taken from `platforms`. The checksums file is only fetched with
+/// `authorize` when it is on the same origin as the artifact.
+pub async fn expected_digest(
+    client: &reqwest::Client,
+    configuration: &File,
+    os: &str,
+    arch: &str,
+    url: &str,
+    file_name: &str,
+    authorize: &Authorize<'_>,
+) -> Result<Digest, Box<dyn std::error::Error>> {
+    let Some(checksums_url) = &configuration.checksums_url else {
+        return platform_digest(configuration, os, arch);
+    };
+    let checksums_url = template::render(checksums_url, &configuration.version, os, arch);
+    let content = if same_origin(url, &checksums_url) {
+        fetch_text(client, &checksums_url, authorize).await?
+    } else {
+        fetch_text(client, &checksums_url, &|request| request).await?
+    };
+    if let Some(signature) = &configuration.checksums_signature {
+        let signature_url =
+            signature::url(signature, &configuration.version, os, arch, &checksums_url);
+        check_signature(
+            client,
+            signature,
+            url,
+            &signature_url,
+            content.as_bytes(),
+            authorize,
+        )
+        .await
+        .map_err(|e| format!("Failed to verify the signature of {}, {}", checksums_url, e))?;
+    }
+    checksums::find(&content, file_name, configuration.checksums_algorithm)
+        .ok_or_else(|| format!("No checksum for {} in {}", file_name, checksums_url).into())
+}
+
+/// Whether `first` and `second` share a scheme, host and port, so that a
+/// credential for one may be sent to the other.
+fn same_origin(first: &str, second: &str) -> bool {
+    let origin = |url: &str| reqwest::Url::parse(url).ok().map(|url| url.origin());
+    matches!((origin(first), origin(second)), (Some(first), Some(second)) if first == second)
+}
+
+pub fn platform_digest(
+    configuration: &File,
+    os: &str,
+    arch: &str,
+) -> Result<Digest, Box<dyn std::error::Error>> {
+    let platform = configuration
+        .platforms
+        .get(os)
+        .ok_or("Platform not found")?;
+    let architecture = platform.get(arch).ok_or("Architecture not found")?;
+    architecture
+        .digest()
+        .map_err(|e| format!("Invalid digest for {}/{}, {}", os, arch, e))?
+        .ok_or_else(|| format!("No digest for {}/{}, set one or checksumsUrl", os, arch).into())
+}
+
+/// Fetches a small text document such as a checksums file or an index.
+pub async fn fetch_text(
+    client: &reqwest::Client,
+    url: &str,
+    authorize: &Authorize<'_>,
+) -> Result<String, String> {
+    let body = fetch_bytes(client, url, authorize).await?;
+    String::from_utf8(body).map_err(|e| format!("Failed to fetch {}, {}", url, e))
+}
+
+/// Fetches a small document that may not be text, such as a signature.
+pub async fn fetch_bytes(
+    client: &reqwest::Client,
+    url: &str,
+    authorize: &Authorize<'_>,
+) -> Result<Vec<u8>, String> {
+    let response = get(client, url, &|request| request, authorize)
+        .await
+        .map_err(|e| format!("Failed to fetch {}, {}", url, e))?;
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to fetch {}, {}", url, e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "Failed to fetch {}, server returned {}: {}",
+            url,
+            status,
+            body_snippet(&String::from_utf8_lossy(&body))
+        ));
+    }
+    Ok(body.to_vec())
+}
+
+/// Hashes the artifact at `url` with `algorithm` as it streams in, without
+/// keeping it, to write the digest into a configuration.
+pub async fn fetch_digest(
+    client: &reqwest::Client,
+    url: &str,
+    algorithm: Algorithm,
+    authorize: &Authorize<'_>,
+) -> Result<Digest, String> {
+    let mut digests = fetch_digests(client, url, &[algorithm], authorize).await?;
+    Ok(digests.remove(0))
+}
+
+/// Hashes the artifact at `url` with each of `algorithms` in a single
+/// download, without keeping it.
+pub async fn fetch_digests(
+    client: &reqwest::Client,
+    url: &str,
+    algorithms: &[Algorithm],
+    authorize: &Authorize<'_>,
+) -> Result<Vec<Digest>, String> {
+    let mut response = get(client, url, &|request| request, authorize)
+        .await
+        .map_err(|e| format!("Failed to download {}, {}", url, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to download {}, server returned {}: {}",
+            url,
+            status,
+            body_snippet(&body)
+        ));
+    }
+
+    let mut hashers: Vec<Hasher> = algorithms
+        .iter()
+        .map(|algorithm| Hasher::new(*algorithm))
+        .collect();
+    let mut progress = Progress::stderr(response.content_length());
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download {}, {}", url, e))?
+    {
+        for hasher in &mut hashers {
+            hasher.update(&chunk);
+        }
+        progress.advance(chunk.len() as u64);
+    }
+    progress.finish();
+    Ok(hashers.into_iter().map(Hasher::finalize).collect())
+}
+
+/// Checks the signature at `signature_url` of the artifact downloaded from
+/// `url` into `destination`, which is removed when it does not verify. The
+/// signature is only fetched with `authorize` from the origin of the artifact.
+pub async fn verify_signature(
+    client: &reqwest::Client,
+    signature: &Signature,
+    url: &str,
+    signature_url: &str,
+    destination: &Path,
+    authorize: &Authorize<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::File::open(destination)
+        .map_err(|e| format!("Failed to read file {}, {}", destination.display(), e))?;
+    if let Err(e) = check_signature(client, signature, url, signature_url, content, authorize).await
+    {
+        let _ = tokio::fs::remove_file(destination).await;
+        return Err(format!("Failed to verify the signature of {}, {}", url, e).into());
+    }
+    Ok(())
+}
+
+/// Checks `content`, read as it is hashed, against the signature at
+/// `signature_url`, fetched with `authorize` only from the origin of the
+/// artifact at `url`.
+async fn check_signature(
+    client: &reqwest::Client,
+    signature: &Signature,
+    url: &str,
+    signature_url: &str,
+    content: impl std::io::Read,
+    authorize: &Authorize<'_>,
+) -> Result<(), String> {
+    let signed = if same_origin(url, signature_url) {
+        fetch_bytes(client, signature_url, authorize).await?
+    } else {
+        fetch_bytes(client, signature_url, &|request| request).await?
+    };
+    signature::check(signature, content, &signed).await
+}
+
+/// Downloads `url` into `destination` and checks its digest, retrying
+/// transient failures as configured.
+pub async fn download_and_validate_digest(
+    client: &reqwest::Client,
+    configuration: &File,
+    url: &str,
+    digest: &Digest,
+    destination: &Path,
+    authorize: &Authorize<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut attempt = 0;
+    loop {
+        match try_download(client, url, digest, destination, authorize).await {
+            Ok(()) => return Ok(()),
+            Err(Failure::Fatal(message)) => return Err(message.into()),
+            Err(Failure::Retryable(message, _)) if attempt >= configuration.retry.retries => {
+                return Err(message.into());
+            }
+            Err(Failure::Retryable(message, retry_after)) => {
+                let delay = retry::retry_delay(
+                    &configuration.retry,
+                    attempt,
+                    retry_after,
+                    retry::random_unit(),
+                );
+                eprintln!(
+                    "Download failed: {}, retrying in {:.1}s ({}/{})",
+                    message,
+                    delay.as_secs_f64(),
+                    attempt + 1,
+                    configuration.retry.retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+        attempt += 1;
+    }
+}
+
+/// Downloads `url` into `destination`, resuming a partial file left by an
+/// earlier attempt when the server still serves the same content.
+async fn try_download(
+    client: &reqwest::Client,
+    url: &str,
+    digest: &Digest,
+    destination: &Path,
+    authorize: &Authorize<'_>,
+) -> Result<(), Failure> {
+    let resume_path = resume_path(destination);
+    let resume_from = resumable_length(url, destination, &resume_path).await;
+
+    let range = |request: reqwest::RequestBuilder| match &resume_from {
+        Some((length, validator)) => request
+            .header(reqwest::header::RANGE, format!("bytes={}-", length))
+            .header(reqwest::header::IF_RANGE, validator),
+        None => request,
+    };
+    let mut response = get(client, url, &range, authorize).await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| retry::parse_retry_after(value, SystemTime::now()));
+        return Err(Failure::Retryable(
+            format!("{} returned {}", url, status),
+            retry_after,
+        ));
+    }
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        discard_partial(destination, &resume_path).await;
+        return Err(Failure::Retryable(
+            format!("{} cannot resume the partial download", url),
+            Some(Duration::ZERO),
+        ));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Failure::Fatal(format!(
+            "Failed to download {}, server returned {}: {}",
+            url,
+            status,
+            body_snippet(&body)
+        )));
+    }
+
+    let resumed_length = resume_from.and_then(|(length, _)| {
+        (status == reqwest::StatusCode::PARTIAL_CONTENT
+            && content_range_start(&response) == Some(length))
+        .then_some(length)
+    });
+    let mut hasher = Hasher::new(digest.algorithm);
+    let mut file = match resumed_length {
+        Some(length) => {
+            eprintln!("Resuming download at {} bytes", length);
+            hash_existing(destination, &mut hasher).await?;
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(destination)
+                .await
+        }
+        None => tokio::fs::File::create(destination).await,
+    }
+    .map_err(|e| {
+        Failure::Fatal(format!(
+            "Failed to open file {}, {}",
+            destination.display(),
+            e
+        ))
+    })?;
+    record_validator(url, &response, &resume_path).await?;
+
+    let mut progress = Progress::stderr(response.content_length());
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(|e| {
+            Failure::Fatal(format!(
+                "Failed to write file {}, {}",
+                destination.display(),
+                e
+            ))
+        })?;
+        progress.advance(chunk.len() as u64);
+    }
+    file.flush().await.map_err(|e| {
+        Failure::Fatal(format!(
+            "Failed to write file {}, {}",
+            destination.display(),
+            e
+        ))
+    })?;
+    progress.finish();
+
+    let actual = hasher.finalize();
+    if actual != *digest {
+        discard_partial(destination, &resume_path).await;
+        return Err(Failure::Fatal(format!(
+            "{} mismatch: expected {}, got {}",
+            digest.algorithm.name().to_uppercase(),
+            digest.hex,
+            actual.hex
+        )));
+    }
+    let _ = tokio::fs::remove_file(&resume_path).await;
+    Ok(())
+}
+
+pub fn resume_path(destination: &Path) -> PathBuf {
+    let mut path = destination.as_os_str().to_owned();
+    path.push(".resume");
+    PathBuf::from(path)
+}
+
+/// Length of the partial file at `destination` and the validator to send
+/// with `If-Range`, when a previous attempt for the same `url` left both.
+async fn resumable_length(
+    url: &str,
+    destination: &Path,
+    resume_path: &Path,
+) -> Option<(u64, String)> {
+    let content = tokio::fs::read_to_string(resume_path).await.ok()?;
+    let partial: PartialDownload = serde_yaml::from_str(&content).ok()?;
+    let length = tokio::fs::metadata(destination).await.ok()?.len();
+    (partial.url == url && length > 0).then_some((length, partial.validator))
+}
+
+/// Remembers how to resume `url` if the transfer breaks off, or forgets it
+/// when the server offers no validator that makes resuming safe.
+async fn record_validator(
+    url: &str,
+    response: &reqwest::Response,
+    resume_path: &Path,
+) -> Result<(), Failure> {
+    let headers = response.headers();
+    let validator = headers
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| {
+            headers
+                .get(reqwest::header::LAST_MODIFIED)
+                .and_then(|value| value.to_str().ok())
+        });
+    let Some(validator) = validator else {
+        let _ = tokio::fs::remove_file(resume_path).await;
+        return Ok(());
+    };
+
+    let partial = PartialDownload {
+        url: url.to_string(),
+        validator: validator.to_string(),
+    };
+    let yaml = serde_yaml::to_string(&partial).map_err(|e| Failure::Fatal(e.to_string()))?;
+    tokio::fs::write(resume_path, yaml).await.map_err(|e| {
+        Failure::Fatal(format!(
+            "Failed to write file {}, {}",
+            resume_path.display(),
+            e
+        ))
+    })
+}
+
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?;
+    let (start, _) = value.strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// The start of a response body on one line, enough to recognise an error
+/// page without flooding the terminal.
+pub fn body_snippet(body: &str) -> String {
+    const MAX_LENGTH: usize = 200;
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    match body.char_indices().nth(MAX_LENGTH) {
+        Some((index, _)) => format!("{}...", &body[..index]),
+        None if body.is_empty() => "<empty body>".to_string(),
+        None => body,
+    }
+}
+
+async fn hash_existing(path: &Path, hasher: &mut Hasher) -> Result<(), Failure> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| Failure::Fatal(format!("Failed to open file {}, {}", path.display(), e)))?;
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|e| {
+            Failure::Fatal(format!("Failed to read file {}, {}", path.display(), e))
+        })?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+async fn discard_partial(destination: &Path, resume_path: &Path) {
+    let _ = tokio::fs::remove_file(destination).await;
+    let _ = tokio::fs::remove_file(resume_path).await;
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use std::net::TcpListener;
+
+    use crate::digest::{Algorithm, Digest, Hasher};
+
+    /// A port mock servers can listen on.
+    pub fn get_available_port() -> Option<u16> {
+        (8000..9000).find(|port| port_is_available(*port))
+    }
+
+    fn port_is_available(port: u16) -> bool {
+        TcpListener::bind(("127.0.0.1", port)).is_ok()
+    }
+
+    pub fn digest_of(algorithm: Algorithm, content: &[u8]) -> Digest {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.update(content);
+        hasher.finalize()
+    }
+
+    pub fn sha256_of(content: &[u8]) -> Digest {
+        digest_of(Algorithm::Sha256, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_snippet_truncates_long_bodies() {
+        // Arrange
+        let body = "a".repeat(500);
+
+        // Act
+        let snippet = body_snippet(&body);
+
+        // Assert
+        assert_eq!(snippet, format!("{}...", "a".repeat(200)));
+        assert_eq!(body_snippet("  "), "<empty body>");
+    }
+
+    #[test]
+    fn test_platform_digest_prefers_digest_then_strongest_algorithm() {
+        // Arrange
+        let configuration = |fields: &str| -> File {
+            serde_yaml::from_str(&format!(
+                "name: tool\nversion: 1.0.0\nplatforms:\n  linux:\n    amd64:\n{}",
+                fields
+            ))
+            .unwrap()
+        };
+        let sha256 = "a".repeat(64);
+        let sha512 = "b".repeat(128);
+
+        // Act
+        let both = configuration(&format!(
+            "      sha256: {}\n      sha512: {}\n",
+            sha256, sha512
+        ));
+        let explicit = configuration(&format!(
+            "      sha512: {}\n      digest: blake3:{}\n",
+            sha512, sha256
+        ));
+        let missing = configuration("      executable: tool\n");
+
+        // Assert
+        assert_eq!(
+            platform_digest(&both, "linux", "amd64")
+                .unwrap()
+                .to_string(),
+            format!("sha512:{}", sha512)
+        );
+        assert_eq!(
+            platform_digest(&explicit, "linux", "amd64")
+                .unwrap()
+                .to_string(),
+            format!("blake3:{}", sha256)
+        );
+        assert!(platform_digest(&missing, "linux", "amd64").is_err());
+    }
+}
@@ -1,9 +1,19 @@
 
 pub mod downloader;
+pub mod http_download;
 pub mod unauthenticated_downloader;
+pub mod authenticated_downloader;
+pub mod github_downloader;
 
 pub mod extractor;
+pub mod binary_extractor;
+pub mod gzip_extractor;
 pub mod zip_extractor;
+pub mod tar_archive;
+pub mod tar_gz_extractor;
+pub mod tar_xz_extractor;
+pub mod tar_bz2_extractor;
+pub mod tar_zst_extractor;
 
 pub mod marker_manager;
 pub mod file_marker_manager;
\ No newline at end of file
@@ -0,0 +1,337 @@
+use std::io::Read;
+use std::path::{Component, Path};
+
+use crate::providers::extractor::entry_destination;
+
+/// Unpacks an uncompressed tar stream into `folder`, removing `strip_prefix`
+/// from every entry and from the targets of hard links. Modes recorded in
+/// the archive are kept. Links that would lead out of `folder` are refused,
+/// so that no later entry can be written through them.
+pub fn unpack<R: Read>(
+    reader: R,
+    folder: &Path,
+    strip_prefix: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(folder)
+        .map_err(|e| format!("Failed to create dir {}, {}", folder.display(), e))?;
+    let root = folder
+        .canonicalize()
+        .map_err(|e| format!("Failed to read dir {}, {}", folder.display(), e))?;
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read tar archive: {}", e))?;
+    let mut extracted_any = false;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read tar entry, {}", e))?;
+        let entry_path = entry.path()?.into_owned();
+        let Some(relative) = entry_destination(&entry_path, strip_prefix)? else {
+            continue;
+        };
+        extracted_any = true;
+
+        let outpath = folder.join(relative);
+        let parent = outpath.parent().unwrap_or(folder);
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create dir {}, {}", parent.display(), e))?;
+        // Directories created so far may be symlinks; only their real
+        // location tells whether the entry stays inside `folder`.
+        let depth = parent
+            .canonicalize()
+            .ok()
+            .and_then(|parent| {
+                parent
+                    .strip_prefix(&root)
+                    .ok()
+                    .map(|inside| inside.components().count())
+            })
+            .ok_or_else(|| outside(&entry_path))?;
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let target = entry
+                .link_name()?
+                .ok_or_else(|| format!("No target for link {}", entry_path.display()))?
+                .into_owned();
+            if std::fs::symlink_metadata(&outpath).is_ok() {
+                std::fs::remove_file(&outpath)
+                    .map_err(|e| format!("Failed to remove file {}, {}", outpath.display(), e))?;
+            }
+            if entry_type.is_symlink() {
+                if !symlink_stays_inside(&target, depth) {
+                    return Err(outside(&entry_path).into());
+                }
+                std::os::unix::fs::symlink(&target, &outpath).map_err(|e| {
+                    format!("Failed to create symlink {}, {}", outpath.display(), e)
+                })?;
+            } else {
+                // The target is another entry of the archive, named by its
+                // path in it.
+                let source = entry_destination(&target, strip_prefix)?
+                    .ok_or_else(|| outside(&entry_path))?;
+                std::fs::hard_link(folder.join(source), &outpath)
+                    .map_err(|e| format!("Failed to extract {}, {}", entry_path.display(), e))?;
+            }
+            continue;
+        }
+        entry
+            .unpack(&outpath)
+            .map_err(|e| format!("Failed to extract {}, {}", entry_path.display(), e))?;
+    }
+
+    if let (Some(prefix), false) = (strip_prefix, extracted_any) {
+        return Err(format!("No entry in the archive starts with prefix {}", prefix).into());
+    }
+    Ok(())
+}
+
+fn outside(entry_path: &Path) -> String {
+    format!(
+        "Refusing to extract {} outside of the target folder",
+        entry_path.display()
+    )
+}
+
+/// Whether the symlink `target`, in a directory `depth` levels below the
+/// folder, points inside it. Only leading `..` are allowed, as one after a
+/// name could climb out of a directory that is itself a symlink.
+fn symlink_stays_inside(target: &Path, depth: usize) -> bool {
+    let mut components = target
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .peekable();
+    let mut climbed = 0;
+    while components.next_if_eq(&Component::ParentDir).is_some() {
+        climbed += 1;
+    }
+    climbed <= depth && components.all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+pub mod test_support {
+    pub fn append_file(builder: &mut tar::Builder<Vec<u8>>, path: &str, mode: u32, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+
+    pub fn append_link(
+        builder: &mut tar::Builder<Vec<u8>>,
+        entry_type: tar::EntryType,
+        path: &str,
+        target: &str,
+    ) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(0);
+        header.set_mode(0o777);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+
+    /// A small archive with a plain file and an executable in a subdirectory.
+    pub fn sample_tar() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        append_file(&mut builder, "README.md", 0o640, b"Hello, world!");
+        append_file(
+            &mut builder,
+            "bin/test_executable",
+            0o750,
+            b"Executable content",
+        );
+        builder.into_inner().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unpack_keeps_file_modes() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let content = test_support::sample_tar();
+
+        // Act
+        unpack(std::io::Cursor::new(content), temp_dir.path(), None).unwrap();
+
+        // Assert
+        let readme_path = temp_dir.path().join("README.md");
+        let executable_path = temp_dir.path().join("bin/test_executable");
+        assert_eq!(fs::read_to_string(&readme_path).unwrap(), "Hello, world!");
+        assert_eq!(
+            fs::read_to_string(&executable_path).unwrap(),
+            "Executable content"
+        );
+        let readme_mode = fs::metadata(&readme_path).unwrap().permissions().mode();
+        assert_eq!(readme_mode & 0o777, 0o640);
+        let executable_mode = fs::metadata(&executable_path).unwrap().permissions().mode();
+        assert_eq!(executable_mode & 0o777, 0o750);
+    }
+
+    #[test]
+    fn test_unpack_refuses_entries_outside_folder() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().join("out");
+        fs::create_dir(&folder).unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        let data = b"evil";
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        // `append_data` rejects `..`, so write the raw name into the header.
+        header.as_old_mut().name[..9].copy_from_slice(b"../escape");
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append(&header, &data[..]).unwrap();
+        let content = builder.into_inner().unwrap();
+
+        // Act
+        let result = unpack(std::io::Cursor::new(content), &folder, None);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(!temp_dir.path().join("escape").exists());
+    }
+
+    #[test]
+    fn test_unpack_refuses_links_out_of_folder() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        let archive = |links: &[(tar::EntryType, &str, &str)]| {
+            let mut builder = tar::Builder::new(Vec::new());
+            for (entry_type, path, target) in links {
+                test_support::append_link(&mut builder, *entry_type, path, target);
+            }
+            test_support::append_file(&mut builder, "bin/x", 0o755, b"evil");
+            builder.into_inner().unwrap()
+        };
+        let symlink = tar::EntryType::Symlink;
+        let absolute = outside.display().to_string();
+
+        // Act
+        let results: Vec<_> = [
+            archive(&[(symlink, "bin", &absolute)]),
+            archive(&[(symlink, "bin", "../outside")]),
+            // `a` is the folder itself, so `a/a/bin` climbs out with `..`.
+            archive(&[(symlink, "a", "."), (symlink, "a/a/bin", "../outside")]),
+            archive(&[(symlink, "bin", "lib/../../outside")]),
+            archive(&[(tar::EntryType::Link, "bin", "../outside")]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, content)| {
+            let folder = temp_dir.path().join(format!("out{}", index));
+            unpack(std::io::Cursor::new(content), &folder, None)
+        })
+        .collect();
+
+        // Assert
+        for result in results {
+            let error = result.unwrap_err().to_string();
+            assert!(error.contains("outside of the target folder"), "{}", error);
+        }
+        assert!(!outside.join("x").exists());
+    }
+
+    #[test]
+    fn test_unpack_links_inside_folder() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        test_support::append_file(&mut builder, "tool-1.0/bin/tool", 0o755, b"tool");
+        test_support::append_link(
+            &mut builder,
+            tar::EntryType::Link,
+            "tool-1.0/bin/tool-alias",
+            "tool-1.0/bin/tool",
+        );
+        test_support::append_link(
+            &mut builder,
+            tar::EntryType::Symlink,
+            "tool-1.0/lib/tool",
+            "../bin/tool",
+        );
+        let content = builder.into_inner().unwrap();
+
+        // Act
+        unpack(
+            std::io::Cursor::new(content),
+            temp_dir.path(),
+            Some("tool-1.0"),
+        )
+        .unwrap();
+
+        // Assert
+        let alias = temp_dir.path().join("bin/tool-alias");
+        assert_eq!(fs::read_to_string(&alias).unwrap(), "tool");
+        assert_eq!(fs::metadata(&alias).unwrap().nlink(), 2);
+        let link = temp_dir.path().join("lib/tool");
+        assert_eq!(fs::read_link(&link).unwrap(), Path::new("../bin/tool"));
+        assert_eq!(fs::read_to_string(&link).unwrap(), "tool");
+    }
+
+    #[test]
+    fn test_unpack_strips_prefix() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        test_support::append_file(
+            &mut builder,
+            "node-v20.0.0-linux-x64/bin/node",
+            0o755,
+            b"node",
+        );
+        test_support::append_file(
+            &mut builder,
+            "node-v20.0.0-linux-x64/LICENSE",
+            0o644,
+            b"MIT",
+        );
+        let content = builder.into_inner().unwrap();
+
+        // Act
+        unpack(
+            std::io::Cursor::new(content),
+            temp_dir.path(),
+            Some("node-v20.0.0-linux-x64"),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("bin/node")).unwrap(),
+            "node"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("LICENSE")).unwrap(),
+            "MIT"
+        );
+        assert!(!temp_dir.path().join("node-v20.0.0-linux-x64").exists());
+    }
+
+    #[test]
+    fn test_unpack_fails_when_prefix_matches_nothing() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let content = test_support::sample_tar();
+
+        // Act
+        let result = unpack(
+            std::io::Cursor::new(content),
+            temp_dir.path(),
+            Some("missing"),
+        );
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
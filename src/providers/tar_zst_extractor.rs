@@ -0,0 +1,90 @@
+use crate::providers::{
+    extractor::{make_executable, open_archive},
+    tar_archive,
+};
+
+pub struct TarZstExtractor {}
+
+impl crate::providers::extractor::Extractor for TarZstExtractor {
+    fn new() -> impl crate::providers::extractor::Extractor {
+        TarZstExtractor {}
+    }
+
+    async fn extract_to_disk(
+        &self,
+        archive_path: &std::path::Path,
+        folder: &std::path::Path,
+        executable_path: &std::path::Path,
+        strip_prefix: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let decoder = zstd::stream::read::Decoder::new(open_archive(archive_path)?)
+            .map_err(|e| format!("Failed to read tar.zst archive: {}", e))?;
+        tar_archive::unpack(decoder, folder, strip_prefix)?;
+        make_executable(executable_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::extractor::Extractor;
+    use crate::providers::tar_archive::test_support::sample_tar;
+
+    use super::*;
+    use crate::providers::extractor::test_support::write_archive;
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_extract_to_disk() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().to_path_buf();
+        let executable_path = folder.join("bin/test_executable");
+
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.write_all(&sample_tar()).unwrap();
+        let content = encoder.finish().unwrap();
+        let archive_path = write_archive(temp_dir.path(), &content);
+
+        // Act
+        let extractor = TarZstExtractor::new();
+        extractor
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
+            .await
+            .unwrap();
+
+        // Assert
+        let readme_path = folder.join("README.md");
+        assert_eq!(fs::read_to_string(&readme_path).unwrap(), "Hello, world!");
+        assert_eq!(
+            fs::read_to_string(&executable_path).unwrap(),
+            "Executable content"
+        );
+
+        let readme_mode = fs::metadata(&readme_path).unwrap().permissions().mode();
+        assert_eq!(readme_mode & 0o777, 0o640);
+        let executable_mode = fs::metadata(&executable_path).unwrap().permissions().mode();
+        assert_eq!(executable_mode & 0o777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_extract_to_disk_fails_on_invalid_archive() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().to_path_buf();
+        let executable_path = folder.join("test_executable");
+        let content = b"not a tarball".to_vec();
+        let archive_path = write_archive(temp_dir.path(), &content);
+
+        // Act
+        let extractor = TarZstExtractor::new();
+        let result = extractor
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
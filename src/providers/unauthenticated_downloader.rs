@@ -1,125 +1,308 @@
-use std::collections::HashMap;
-use sha2::Digest;
+use std::path::Path;
+use crate::digest::{Algorithm, Digest};
 use crate::models::configuration::File;
-use bytes::Bytes;
+use crate::providers::http_download;
+use crate::signature;
 
-static ARCH_MAPPING: std::sync::LazyLock<HashMap<&str, &str>> = std::sync::LazyLock::new(|| {
-    let mut map = HashMap::new();
-    map.insert("x86_64", "amd64");
-    map.insert("aarch64", "arm64");
-    map
-});
-
-pub struct UnauthenticatedDownloader {}
+pub struct UnauthenticatedDownloader {
+    client: reqwest::Client,
+}
 
 impl crate::providers::downloader::Downloader for UnauthenticatedDownloader {
     fn new() -> impl super::downloader::Downloader {
-        UnauthenticatedDownloader{}
+        UnauthenticatedDownloader {
+            client: http_download::client(),
+        }
     }
 
-    async fn build_url_and_sha256(
+    async fn build_url_and_digest(
         &self,
         configuration: &File,
         os: &str,
         arch: &str,
-    ) -> Result<(String, String), Box<dyn std::error::Error>> {
-        let arch = ARCH_MAPPING.get(arch).ok_or("Architecture not found")?;
-        let platform = configuration
-            .platforms
-            .get(os)
-            .ok_or("Platform not found")?;
-        let architecture = platform.get(*arch).ok_or("Architecture not found")?;
-        let url = configuration
-            .url_pattern
-            .replace("{{version}}", &configuration.version)
-            .replace("{{os}}", os)
-            .replace("{{arch}}", arch);
-        Ok((url, architecture.sha256.clone()))
-    }
-
-    async fn download_and_validate_sha256(
+    ) -> Result<(String, Digest), Box<dyn std::error::Error>> {
+        let url = http_download::build_url(configuration, os, arch)?;
+        let digest = http_download::expected_digest(
+            &self.client,
+            configuration,
+            os,
+            arch,
+            &url,
+            &http_download::file_name(&url),
+            &|request| request,
+        )
+        .await?;
+        Ok((url, digest))
+    }
+
+    async fn download_and_validate_digest(
         &self,
+        configuration: &File,
         url: &str,
-        sha256: &str,
-    ) -> Result<Bytes, Box<dyn std::error::Error>> {
-        let response = reqwest::get(url).await?;
-        let bytes = response.bytes().await?;
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(&bytes);
-        let result = hasher.finalize();
-        let hash = format!("{:x}", result);
-        if hash != sha256 {
-            return Err(format!("SHA256 mismatch: expected {}, got {}", sha256, hash).into());
-        }
-        Ok(bytes)
+        digest: &Digest,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        http_download::download_and_validate_digest(
+            &self.client,
+            configuration,
+            url,
+            digest,
+            destination,
+            &|request| request,
+        )
+        .await
     }
-}
 
+    async fn verify_signature(
+        &self,
+        configuration: &File,
+        os: &str,
+        arch: &str,
+        url: &str,
+        destination: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(signature) = &configuration.signature else {
+            return Ok(());
+        };
+        let signature_url = signature::url(signature, &configuration.version, os, arch, url);
+        http_download::verify_signature(
+            &self.client,
+            signature,
+            url,
+            &signature_url,
+            destination,
+            &|request| request,
+        )
+        .await
+    }
+
+    async fn fetch_digests(
+        &self,
+        _configuration: &File,
+        url: &str,
+        algorithms: &[Algorithm],
+    ) -> Result<Vec<Digest>, Box<dyn std::error::Error>> {
+        Ok(
+            http_download::fetch_digests(&self.client, url, algorithms, &|request| request)
+                .await?,
+        )
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::providers::downloader::Downloader;
 
     use super::*;
+    use crate::models::partial_download::PartialDownload;
+    use crate::providers::http_download::resume_path;
+    use crate::digest::Algorithm;
+    use crate::providers::http_download::test_support::{digest_of, get_available_port, sha256_of};
     use iron::{status, Iron, IronResult, Listening, Request, Response};
 
-    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    fn get_available_port() -> Option<u16> {
-        (8000..9000).find(|port| port_is_available(*port))
-    }
+    fn mock_server(content: &'static [u8]) -> (Listening, u16) {
+        let handler = move |_req: &mut Request| -> IronResult<Response> {
+            Ok(Response::with((status::Ok, content)))
+        };
 
-    fn port_is_available(port: u16) -> bool {
-        TcpListener::bind(("127.0.0.1", port)).is_ok()
+        mock_handler_server(handler)
     }
 
-    fn mock_server(content: &'static [u8]) -> (Listening, u16) {
+    fn mock_handler_server<H: iron::Handler>(handler: H) -> (Listening, u16) {
         let port = get_available_port().expect("No available port found");
 
+        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+    }
+
+    /// Answers with `failure` for the first `failures` requests, then with
+    /// `content`. Returns the number of requests served so far as well.
+    fn flaky_server(
+        failures: usize,
+        failure: status::Status,
+        content: &'static [u8],
+    ) -> (Listening, u16, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
         let handler = move |_req: &mut Request| -> IronResult<Response> {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                let mut response = Response::with((failure, "try again"));
+                response.headers.set_raw("Retry-After", vec![b"0".to_vec()]);
+                return Ok(response);
+            }
             Ok(Response::with((status::Ok, content)))
         };
 
-        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+        let (server, port) = mock_handler_server(handler);
+        (server, port, requests)
+    }
+
+    /// Serves `content` with `etag`, answering a `Range` request whose
+    /// `If-Range` matches with the rest of it. Returns the `Range` headers
+    /// received as well.
+    fn ranged_server(
+        content: &'static [u8],
+        etag: &'static str,
+    ) -> (Listening, u16, Arc<std::sync::Mutex<Vec<String>>>) {
+        let ranges = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = ranges.clone();
+        let handler = move |req: &mut Request| -> IronResult<Response> {
+            let header = |name: &str| {
+                req.headers
+                    .get_raw(name)
+                    .map(|values| String::from_utf8_lossy(&values[0]).to_string())
+            };
+            let start = header("Range")
+                .filter(|_| header("If-Range").as_deref() == Some(etag))
+                .and_then(|range| {
+                    range
+                        .strip_prefix("bytes=")?
+                        .strip_suffix('-')?
+                        .parse()
+                        .ok()
+                });
+            if let Some(range) = header("Range") {
+                seen.lock().unwrap().push(range);
+            }
+
+            let mut response = match start {
+                Some(start) => {
+                    let mut response = Response::with((status::PartialContent, &content[start..]));
+                    let content_range =
+                        format!("bytes {}-{}/{}", start, content.len() - 1, content.len());
+                    response
+                        .headers
+                        .set_raw("Content-Range", vec![content_range.into_bytes()]);
+                    response
+                }
+                None => Response::with((status::Ok, content)),
+            };
+            response
+                .headers
+                .set_raw("ETag", vec![etag.as_bytes().to_vec()]);
+            Ok(response)
+        };
+
+        let (server, port) = mock_handler_server(handler);
+        (server, port, ranges)
+    }
+
+    async fn write_partial(destination: &Path, url: &str, content: &[u8], validator: &str) {
+        tokio::fs::write(destination, content).await.unwrap();
+        let partial = PartialDownload {
+            url: url.to_string(),
+            validator: validator.to_string(),
+        };
+        tokio::fs::write(
+            resume_path(destination),
+            serde_yaml::to_string(&partial).unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    fn configuration(retries: u32) -> File {
+        let mut configuration: File = serde_yaml::from_str(
+            r#"
+name: test
+version: 1.0.0
+urlPattern: http://127.0.0.1/test
+platforms: {}
+"#,
+        )
+        .unwrap();
+        configuration.retry = crate::models::configuration::RetryConfiguration {
+            retries,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 10,
+            jitter: 0.0,
+        };
+        configuration
     }
 
     #[tokio::test]
-    async fn test_download_and_validate_sha256_success() {
+    async fn test_download_and_validate_digest_success() {
         // Arrange
         let content = b"test content";
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(content);
-        let sha256 = format!("{:x}", hasher.finalize());
+        let sha256 = sha256_of(content);
 
         let (mut server, port) = mock_server(content);
         let url = format!("http://127.0.0.1:{}", port);
 
         let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
 
         // Act
-        let result = downloader.download_and_validate_sha256(&url, &sha256).await;
+        let result = downloader
+            .download_and_validate_digest(&configuration(0), &url, &sha256, &destination)
+            .await;
 
         // Assert
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Bytes::from_static(content));
+        assert_eq!(std::fs::read(&destination).unwrap(), content);
 
         // Clean up
         server.close().unwrap();
     }
 
     #[tokio::test]
-    async fn test_download_and_validate_sha256_mismatch() {
+    async fn test_download_and_validate_digest_with_sha512() {
         // Arrange
         let content = b"test content";
-        let incorrect_sha256 = "incorrectsha256";
+        let (mut server, port) = mock_server(content);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let valid = downloader
+            .download_and_validate_digest(
+                &configuration(0),
+                &url,
+                &digest_of(Algorithm::Sha512, content),
+                &destination,
+            )
+            .await;
+        let invalid = downloader
+            .download_and_validate_digest(
+                &configuration(0),
+                &url,
+                &digest_of(Algorithm::Blake3, b"incorrect"),
+                &destination,
+            )
+            .await;
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid.err());
+        let error = invalid.err().unwrap().to_string();
+        assert!(error.contains("BLAKE3 mismatch"), "{}", error);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_and_validate_digest_mismatch() {
+        // Arrange
+        let content = b"test content";
+        let incorrect_sha256 = sha256_of(b"incorrect");
 
         let (mut server, port) = mock_server(content);
         let url = format!("http://127.0.0.1:{}", port);
 
         let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
 
         // Act
-        let result = downloader.download_and_validate_sha256(&url, incorrect_sha256).await;
+        let result = downloader
+            .download_and_validate_digest(&configuration(0), &url, &incorrect_sha256, &destination)
+            .await;
 
         // Assert
         assert!(result.is_err());
@@ -128,8 +311,496 @@ mod tests {
             .unwrap()
             .to_string()
             .contains("SHA256 mismatch"));
-        
+        assert!(!destination.exists());
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_server_errors() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, requests) = flaky_server(2, status::ServiceUnavailable, content);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(&configuration(3), &url, &sha256_of(content), &destination)
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(std::fs::read(&destination).unwrap(), content);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_too_many_requests() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, requests) = flaky_server(1, status::TooManyRequests, content);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(&configuration(1), &url, &sha256_of(content), &destination)
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_caps_retry_after_at_max_backoff() {
+        // Arrange
+        let content = b"test content";
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let handler = move |_req: &mut Request| -> IronResult<Response> {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                let mut response = Response::with((status::ServiceUnavailable, "try tomorrow"));
+                response.headers.set_raw("Retry-After", vec![b"86400".to_vec()]);
+                return Ok(response);
+            }
+            Ok(Response::with((status::Ok, &content[..])))
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            downloader.download_and_validate_digest(
+                &configuration(1),
+                &url,
+                &sha256_of(content),
+                &destination,
+            ),
+        )
+        .await;
+
+        // Assert
+        assert!(matches!(result, Ok(Ok(()))));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_gives_up_after_configured_retries() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, requests) = flaky_server(10, status::InternalServerError, content);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(&configuration(2), &url, &sha256_of(content), &destination)
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("500"));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_does_not_retry_sha256_mismatch() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, requests) = flaky_server(0, status::InternalServerError, content);
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(
+                &configuration(3),
+                &url,
+                &sha256_of(b"incorrect"),
+                &destination,
+            )
+            .await;
+
+        // Assert
+        assert!(result.err().unwrap().to_string().contains("SHA256 mismatch"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, ranges) = ranged_server(content, "\"v1\"");
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+        write_partial(&destination, &url, b"test ", "\"v1\"").await;
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(
+                &configuration(0),
+                &url,
+                &sha256_of(content),
+                &destination,
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert_eq!(*ranges.lock().unwrap(), vec!["bytes=5-".to_string()]);
+        assert_eq!(tokio::fs::read(&destination).await.unwrap(), content);
+        assert!(
+            tokio::fs::metadata(resume_path(&destination))
+                .await
+                .is_err()
+        );
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_content_changed() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, ranges) = ranged_server(content, "\"v2\"");
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+        write_partial(&destination, &url, b"old c", "\"v1\"").await;
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(
+                &configuration(0),
+                &url,
+                &sha256_of(content),
+                &destination,
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert_eq!(ranges.lock().unwrap().len(), 1);
+        assert_eq!(tokio::fs::read(&destination).await.unwrap(), content);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_ignores_partial_file_of_another_url() {
+        // Arrange
+        let content = b"test content";
+        let (mut server, port, ranges) = ranged_server(content, "\"v1\"");
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+        write_partial(&destination, "http://example.com/other", b"test ", "\"v1\"").await;
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(
+                &configuration(0),
+                &url,
+                &sha256_of(content),
+                &destination,
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert!(ranges.lock().unwrap().is_empty());
+        assert_eq!(tokio::fs::read(&destination).await.unwrap(), content);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_not_found() {
+        // Arrange
+        let handler = |_req: &mut Request| -> IronResult<Response> {
+            Ok(Response::with((
+                status::NotFound,
+                "<html>\n  <body>Not Found</body>\n</html>",
+            )))
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let url = format!("http://127.0.0.1:{}/missing", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(&configuration(3), &url, &sha256_of(b""), &destination)
+            .await;
+
+        // Assert
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains(&url), "{}", error);
+        assert!(error.contains("404 Not Found"), "{}", error);
+        assert!(
+            error.contains("<html> <body>Not Found</body> </html>"),
+            "{}",
+            error
+        );
+        assert!(!error.contains("SHA256 mismatch"), "{}", error);
+        assert!(tokio::fs::metadata(&destination).await.is_err());
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_does_not_retry_client_errors() {
+        // Arrange
+        let (mut server, port, requests) = flaky_server(5, status::Forbidden, b"test content");
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+
+        // Act
+        let result = downloader
+            .download_and_validate_digest(&configuration(3), &url, &sha256_of(b""), &destination)
+            .await;
+
+        // Assert
+        assert!(result.err().unwrap().to_string().contains("403 Forbidden"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_build_url_and_digest_reads_checksums_file() {
+        // Arrange
+        let checksums = format!(
+            "{}  ./tool_1.2.0_darwin_arm64.tar.gz\n{}  ./tool_1.2.0_linux_amd64.tar.gz\n",
+            sha256_of(b"darwin").hex,
+            sha256_of(b"linux").hex
+        );
+        let handler = move |req: &mut Request| -> IronResult<Response> {
+            match req.url.path().join("/").as_str() {
+                "v1.2.0/SHA256SUMS" => Ok(Response::with((status::Ok, checksums.clone()))),
+                _ => Ok(Response::with(status::NotFound)),
+            }
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let mut configuration = configuration(0);
+        configuration.version = "1.2.0".to_string();
+        configuration.url_pattern = format!(
+            "http://127.0.0.1:{}/v{{{{version}}}}/tool_{{{{version}}}}_{{{{os}}}}_{{{{arch}}}}.tar.gz",
+            port
+        );
+        configuration.checksums_url =
+            Some(format!("http://127.0.0.1:{}/v{{{{version}}}}/SHA256SUMS", port));
+
+        let downloader = UnauthenticatedDownloader::new();
+
+        // Act
+        let result = downloader
+            .build_url_and_digest(&configuration, "linux", "amd64")
+            .await;
+
+        // Assert
+        let (url, sha256) = result.unwrap();
+        assert!(url.ends_with("/v1.2.0/tool_1.2.0_linux_amd64.tar.gz"));
+        assert_eq!(sha256, sha256_of(b"linux"));
+
         // Clean up
         server.close().unwrap();
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_build_url_and_digest_without_checksum_for_file_fails() {
+        // Arrange
+        let handler = |_req: &mut Request| -> IronResult<Response> {
+            Ok(Response::with((status::Ok, "")))
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let mut configuration = configuration(0);
+        configuration.url_pattern = format!("http://127.0.0.1:{}/tool.tar.gz", port);
+        configuration.checksums_url = Some(format!("http://127.0.0.1:{}/SHA256SUMS", port));
+
+        let downloader = UnauthenticatedDownloader::new();
+
+        // Act
+        let result = downloader
+            .build_url_and_digest(&configuration, "linux", "amd64")
+            .await;
+
+        // Assert
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("No checksum for tool.tar.gz"), "{}", error);
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_rejects_tampered_signature() {
+        // Arrange
+        // A minisign signature of `test`, from the minisign-verify examples.
+        static SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==
+";
+        let handler = |req: &mut Request| -> IronResult<Response> {
+            match req.url.path().join("/").as_str() {
+                "tool" => Ok(Response::with((status::Ok, "test"))),
+                "tool.minisig" => Ok(Response::with((status::Ok, SIGNATURE))),
+                "tampered.minisig" => Ok(Response::with((
+                    status::Ok,
+                    SIGNATURE.replace("file:test", "file:tool"),
+                ))),
+                _ => Ok(Response::with(status::NotFound)),
+            }
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let url = format!("http://127.0.0.1:{}/tool", port);
+        let mut configuration = configuration(0);
+        configuration.signature = Some(crate::models::configuration::Signature {
+            format: crate::models::configuration::SignatureFormat::Minisign,
+            url: None,
+            public_key: Some(
+                "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".to_string(),
+            ),
+            keyring: None,
+            identity: None,
+            issuer: None,
+            trust_root: None,
+        });
+
+        let downloader = UnauthenticatedDownloader::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("download");
+        std::fs::write(&destination, "test").unwrap();
+
+        // Act
+        let valid = downloader
+            .verify_signature(&configuration, "linux", "amd64", &url, &destination)
+            .await;
+        configuration.signature.as_mut().unwrap().url =
+            Some(format!("http://127.0.0.1:{}/tampered.minisig", port));
+        let tampered = downloader
+            .verify_signature(&configuration, "linux", "amd64", &url, &destination)
+            .await;
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid.err());
+        let error = tampered.err().unwrap().to_string();
+        assert!(error.contains("Failed to verify the signature"), "{}", error);
+        assert!(!destination.exists());
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_build_url_and_digest_verifies_signed_checksums() {
+        // Arrange
+        use crate::openpgp::tests::{CONTENT, RSA_KEY, RSA_SIGNATURE};
+        let handler = |req: &mut Request| -> IronResult<Response> {
+            match req.url.path().join("/").as_str() {
+                "SHA256SUMS" => Ok(Response::with((status::Ok, CONTENT))),
+                "tampered/SHA256SUMS" => Ok(Response::with((
+                    status::Ok,
+                    CONTENT.replace('a', "b"),
+                ))),
+                "SHA256SUMS.sig" | "tampered/SHA256SUMS.sig" => {
+                    Ok(Response::with((status::Ok, RSA_SIGNATURE)))
+                }
+                _ => Ok(Response::with(status::NotFound)),
+            }
+        };
+        let (mut server, port) = mock_handler_server(handler);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let keyring = temp_dir.path().join("keyring.asc");
+        std::fs::write(&keyring, RSA_KEY).unwrap();
+        let mut configuration = configuration(0);
+        configuration.url_pattern = format!(
+            "http://127.0.0.1:{}/tool_{{{{version}}}}_{{{{os}}}}_{{{{arch}}}}.zip",
+            port
+        );
+        configuration.checksums_url = Some(format!("http://127.0.0.1:{}/SHA256SUMS", port));
+        configuration.checksums_signature = Some(crate::models::configuration::Signature {
+            format: crate::models::configuration::SignatureFormat::Openpgp,
+            url: None,
+            public_key: None,
+            keyring: Some(keyring.display().to_string()),
+            identity: None,
+            issuer: None,
+            trust_root: None,
+        });
+
+        let downloader = UnauthenticatedDownloader::new();
+
+        // Act
+        let valid = downloader
+            .build_url_and_digest(&configuration, "linux", "amd64")
+            .await;
+        configuration.checksums_url =
+            Some(format!("http://127.0.0.1:{}/tampered/SHA256SUMS", port));
+        let tampered = downloader
+            .build_url_and_digest(&configuration, "linux", "amd64")
+            .await;
+
+        // Assert
+        let (_, digest) = valid.unwrap();
+        assert_eq!(digest.hex, "a".repeat(64));
+        let error = tampered.err().unwrap().to_string();
+        assert!(error.contains("Signature does not match"), "{}", error);
+
+        // Clean up
+        server.close().unwrap();
+    }
+}
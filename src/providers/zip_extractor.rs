@@ -1,6 +1,4 @@
-use std::{io::Read, os::unix::fs::PermissionsExt};
-
-use tokio::io::AsyncWriteExt;
+use crate::providers::extractor::{entry_destination, make_executable, open_archive};
 
 pub struct ZipExtractor {}
 
@@ -11,23 +9,30 @@ impl crate::providers::extractor::Extractor for ZipExtractor {
 
     async fn extract_to_disk(
         &self,
-        content: &bytes::Bytes,
-        folder: &std::path::PathBuf,
-        executable_path: &std::path::PathBuf,
+        archive_path: &std::path::Path,
+        folder: &std::path::Path,
+        executable_path: &std::path::Path,
+        strip_prefix: Option<&str>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let mut archive =
-            zip::ZipArchive::new(std::io::Cursor::new(content.to_vec())).or_else(|e| {
+            zip::ZipArchive::new(open_archive(archive_path)?).or_else(|e| {
                 Err::<_, Box<dyn std::error::Error>>(
                     format!("Failed to read zip archive: {}", e).into(),
                 )
             })?;
+        let mut extracted_any = false;
         for i in 0..archive.len() {
             let mut file = archive.by_index(i).or_else(|e| {
                 Err::<_, Box<dyn std::error::Error>>(
                     format!("Failed to get by index {}, {}", i, e).into(),
                 )
             })?;
-            let outpath = folder.join(file.name());
+            let entry_path = std::path::PathBuf::from(file.name());
+            let Some(relative) = entry_destination(&entry_path, strip_prefix)? else {
+                continue;
+            };
+            extracted_any = true;
+            let outpath = folder.join(relative);
             if file.name().ends_with('/') {
                 tokio::fs::create_dir_all(&outpath).await.or_else(|e| {
                     Err::<_, Box<dyn std::error::Error>>(
@@ -42,18 +47,12 @@ impl crate::providers::extractor::Extractor for ZipExtractor {
                         )
                     })?;
                 }
-                let mut outfile = tokio::fs::File::create(&outpath).await.or_else(|e| {
+                let mut outfile = std::fs::File::create(&outpath).or_else(|e| {
                     Err::<_, Box<dyn std::error::Error>>(
                         format!("Failed to create file {}, {}", outpath.display(), e).into(),
                     )
                 })?;
-                let mut buffer = Vec::new();
-                file.read_to_end(&mut buffer).or_else(|e| {
-                    Err::<_, Box<dyn std::error::Error>>(
-                        format!("Failed to read file {}, {}", file.name(), e).into(),
-                    )
-                })?;
-                outfile.write_all(&buffer).await.or_else(|e| {
+                std::io::copy(&mut file, &mut outfile).or_else(|e| {
                     Err::<_, Box<dyn std::error::Error>>(
                         format!("Failed to write file {}, {}", outpath.display(), e).into(),
                     )
@@ -61,21 +60,10 @@ impl crate::providers::extractor::Extractor for ZipExtractor {
             }
         }
 
-        let mut permissions = tokio::fs::metadata(&executable_path).await?.permissions();
-        permissions.set_mode(0o755);
-        tokio::fs::set_permissions(&executable_path, permissions)
-            .await
-            .or_else(|e| {
-                Err::<_, Box<dyn std::error::Error>>(
-                    format!(
-                        "Failed to set permissions {}, {}",
-                        executable_path.display(),
-                        e
-                    )
-                    .into(),
-                )
-            })?;
-        Ok(())
+        if let (Some(prefix), false) = (strip_prefix, extracted_any) {
+            return Err(format!("No entry in the archive starts with prefix {}", prefix).into());
+        }
+        make_executable(executable_path).await
     }
 }
 
@@ -85,9 +73,10 @@ mod tests {
     use crate::providers::extractor::Extractor;
 
     use super::*;
-    use bytes::Bytes;
+    use crate::providers::extractor::test_support::write_archive;
     use std::fs;
     use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -117,12 +106,12 @@ mod tests {
             zip_writer.finish().unwrap();
         }
 
-        let content = Bytes::from(zip_buffer);
+        let archive_path = write_archive(temp_dir.path(), &zip_buffer);
 
         // Act
         let extractor = ZipExtractor::new();
         extractor
-            .extract_to_disk(&content, &folder, &executable_path)
+            .extract_to_disk(&archive_path, &folder, &executable_path, None)
             .await
             .unwrap();
 
@@ -142,6 +131,43 @@ mod tests {
         let metadata = fs::metadata(&extracted_executable_path).unwrap();
         assert!(metadata.permissions().mode() & 0o755 != 0);
     }
-}
 
+    #[tokio::test]
+    async fn test_extract_to_disk_strips_prefix() {
+        // Arrange
+        let temp_dir = tempdir().unwrap();
+        let folder = temp_dir.path().to_path_buf();
+        let executable_path = folder.join("bin/node");
 
+        let mut zip_buffer = Vec::new();
+        {
+            let mut zip_writer = zip::ZipWriter::new(std::io::Cursor::new(&mut zip_buffer));
+            let options: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+            zip_writer
+                .add_directory("node-v20.0.0-win-x64/", options)
+                .unwrap();
+            zip_writer
+                .start_file("node-v20.0.0-win-x64/bin/node", options)
+                .unwrap();
+            zip_writer.write_all(b"node").unwrap();
+            zip_writer.finish().unwrap();
+        }
+        let archive_path = write_archive(temp_dir.path(), &zip_buffer);
+
+        // Act
+        let extractor = ZipExtractor::new();
+        extractor
+            .extract_to_disk(
+                &archive_path,
+                &folder,
+                &executable_path,
+                Some("node-v20.0.0-win-x64"),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(fs::read_to_string(&executable_path).unwrap(), "node");
+        assert!(!folder.join("node-v20.0.0-win-x64").exists());
+    }
+}
@@ -0,0 +1,129 @@
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+use crate::models::configuration::RetryConfiguration;
+
+/// Delay before retry number `attempt` (starting at 0): the initial backoff
+/// doubled per attempt, capped, then spread by `jitter`. `random` is a value
+/// in `[0, 1)`.
+pub fn backoff_delay(retry: &RetryConfiguration, attempt: u32, random: f64) -> Duration {
+    let base = retry
+        .initial_backoff_ms
+        .saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX))
+        .min(retry.max_backoff_ms) as f64;
+    let jitter = retry.jitter.clamp(0.0, 1.0);
+    let delay = base * (1.0 - jitter + 2.0 * jitter * random);
+    Duration::from_millis(delay.min(retry.max_backoff_ms as f64) as u64)
+}
+
+/// Delay before retry number `attempt`: what the server asked for in
+/// `Retry-After`, capped at the maximum backoff so that a server cannot stall
+/// the launch, or the backoff otherwise.
+pub fn retry_delay(
+    retry: &RetryConfiguration,
+    attempt: u32,
+    retry_after: Option<Duration>,
+    random: f64,
+) -> Duration {
+    match retry_after {
+        Some(retry_after) => retry_after.min(Duration::from_millis(retry.max_backoff_ms)),
+        None => backoff_delay(retry, attempt, random),
+    }
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// A random value in `[0, 1)`, good enough to spread retries apart.
+pub fn random_unit() -> f64 {
+    let value = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry(jitter: f64) -> RetryConfiguration {
+        RetryConfiguration {
+            retries: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            jitter,
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_and_caps() {
+        let retry = retry(0.0);
+
+        assert_eq!(backoff_delay(&retry, 0, 0.5), Duration::from_millis(100));
+        assert_eq!(backoff_delay(&retry, 1, 0.5), Duration::from_millis(200));
+        assert_eq!(backoff_delay(&retry, 3, 0.5), Duration::from_millis(800));
+        assert_eq!(backoff_delay(&retry, 4, 0.5), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(&retry, 80, 0.5), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_delay_applies_jitter() {
+        let retry = retry(0.5);
+
+        assert_eq!(backoff_delay(&retry, 1, 0.0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(&retry, 1, 0.5), Duration::from_millis(200));
+        assert_eq!(backoff_delay(&retry, 1, 0.99), Duration::from_millis(298));
+    }
+
+    #[test]
+    fn test_retry_delay_caps_retry_after() {
+        let retry = retry(0.0);
+
+        assert_eq!(
+            retry_delay(&retry, 0, Some(Duration::from_millis(300)), 0.5),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            retry_delay(&retry, 0, Some(Duration::from_secs(86400)), 0.5),
+            Duration::from_millis(1000)
+        );
+        assert_eq!(
+            retry_delay(&retry, 2, None, 0.5),
+            Duration::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_random_unit_is_in_range() {
+        for _ in 0..100 {
+            let value = random_unit();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+}
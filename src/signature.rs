@@ -0,0 +1,356 @@
+use std::io::Read;
+
+use base64::Engine;
+
+use crate::models::configuration::{Signature, SignatureFormat};
+use crate::{cosign, openpgp, template};
+
+/// DER prefix of an Ed25519 `SubjectPublicKeyInfo`, as in the PEM files
+/// `openssl pkey -pubout` writes; the 32 bytes of the key follow it.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// The size of the chunks signed content is read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The URL of the signature of the artifact at `artifact_url`.
+pub fn url(
+    signature: &Signature,
+    version: &str,
+    os: &str,
+    arch: &str,
+    artifact_url: &str,
+) -> String {
+    let pattern = signature
+        .url
+        .clone()
+        .unwrap_or_else(|| format!("{{{{url}}}}{}", extension(signature.format)));
+    template::render(&pattern, version, os, arch).replace("{{url}}", artifact_url)
+}
+
+/// The suffix signatures of `format` are usually published with.
+pub fn extension(format: SignatureFormat) -> &'static str {
+    match format {
+        SignatureFormat::Minisign => ".minisig",
+        SignatureFormat::Ed25519 | SignatureFormat::Openpgp => ".sig",
+        SignatureFormat::Cosign => ".bundle",
+    }
+}
+
+/// The trusted key of `configuration`, given inline or read from its
+/// keyring.
+pub async fn trusted_key(configuration: &Signature) -> Result<Vec<u8>, String> {
+    match (&configuration.public_key, &configuration.keyring) {
+        (Some(public_key), _) => Ok(public_key.as_bytes().to_vec()),
+        (None, Some(keyring)) => {
+            let path = shellexpand::tilde(keyring);
+            tokio::fs::read(path.as_ref())
+                .await
+                .map_err(|e| format!("Failed to read file {}, {}", path, e))
+        }
+        (None, None) => Err("A signature requires publicKey or keyring".to_string()),
+    }
+}
+
+/// Checks that `signed` is a signature of `content` trusted by
+/// `configuration`.
+pub async fn check(
+    configuration: &Signature,
+    content: impl Read,
+    signed: &[u8],
+) -> Result<(), String> {
+    match configuration.format {
+        SignatureFormat::Cosign => cosign::verify(configuration, content, signed),
+        format => verify(format, &trusted_key(configuration).await?, content, signed),
+    }
+}
+
+/// Checks that `signature`, in `format`, was made over `content` with `key`.
+pub fn verify(
+    format: SignatureFormat,
+    key: &[u8],
+    content: impl Read,
+    signature: &[u8],
+) -> Result<(), String> {
+    let key_text =
+        || std::str::from_utf8(key).map_err(|_| "The public key is not text".to_string());
+    match format {
+        SignatureFormat::Minisign => verify_minisign(key_text()?, content, signature),
+        SignatureFormat::Ed25519 => verify_ed25519(key_text()?, content, signature),
+        SignatureFormat::Openpgp => openpgp::verify(key, content, signature),
+        SignatureFormat::Cosign => {
+            Err("Cosign bundles are checked against a trustRoot, not a key".to_string())
+        }
+    }
+}
+
+/// Feeds `content` to `update` a chunk at a time, so that it is hashed
+/// without being held in memory.
+pub fn read_chunks(mut content: impl Read, mut update: impl FnMut(&[u8])) -> Result<(), String> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        match content.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => update(&buffer[..read]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Failed to read the signed content, {}", e)),
+        }
+    }
+}
+
+/// All of `content`, for signatures made over the content rather than its
+/// hash.
+fn read_all(mut content: impl Read) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    content
+        .read_to_end(&mut buffer)
+        .map_err(|e| format!("Failed to read the signed content, {}", e))?;
+    Ok(buffer)
+}
+
+fn verify_minisign(public_key: &str, content: impl Read, signature: &[u8]) -> Result<(), String> {
+    // The key on its own, or a `minisign.pub` file with its comment first.
+    let public_key = public_key
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+        .unwrap_or_default();
+    let public_key = minisign_verify::PublicKey::from_base64(public_key)
+        .map_err(|e| format!("Invalid minisign public key, {}", e))?;
+    let signature = std::str::from_utf8(signature)
+        .map_err(|e| e.to_string())
+        .and_then(|signature| {
+            minisign_verify::Signature::decode(signature.trim()).map_err(|e| e.to_string())
+        })
+        .map_err(|e| format!("Invalid minisign signature, {}", e))?;
+    match public_key.verify_stream(&signature) {
+        Ok(mut verifier) => {
+            read_chunks(content, |chunk| verifier.update(chunk))?;
+            verifier.finalize().map_err(|e| e.to_string())
+        }
+        // Legacy signatures, made by minisign before 0.8, sign the content
+        // itself rather than its hash, which is just as safe.
+        Err(minisign_verify::Error::UnsupportedLegacyMode) => public_key
+            .verify(&read_all(content)?, &signature, true)
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Ed25519 signs the content itself, which has to be read whole.
+fn verify_ed25519(public_key: &str, content: impl Read, signature: &[u8]) -> Result<(), String> {
+    let key = decode_text(public_key)
+        .and_then(|key| match key.strip_prefix(&ED25519_SPKI_PREFIX) {
+            Some(key) => key.try_into().ok(),
+            None => key.try_into().ok(),
+        })
+        .ok_or("Invalid Ed25519 public key, expected 32 bytes in base64, hex or PEM")?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+        .map_err(|e| format!("Invalid Ed25519 public key, {}", e))?;
+    let signature: [u8; 64] = match signature.try_into() {
+        Ok(signature) => signature,
+        Err(_) => std::str::from_utf8(signature)
+            .ok()
+            .and_then(decode_text)
+            .and_then(|signature| signature.try_into().ok())
+            .ok_or("Invalid Ed25519 signature, expected 64 bytes, raw or in base64 or hex")?,
+    };
+    let content = read_all(content)?;
+    key.verify_strict(&content, &ed25519_dalek::Signature::from_bytes(&signature))
+        .map_err(|_| "Signature does not match".to_string())
+}
+
+/// Bytes written in hex, base64, or base64 with PEM armor.
+fn decode_text(text: &str) -> Option<Vec<u8>> {
+    let text: String = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    decode_hex(&text).or_else(|| base64::engine::general_purpose::STANDARD.decode(&text).ok())
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+
+    // The example from the minisign-verify documentation, a prehashed
+    // signature of `test`.
+    static MINISIGN_PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    static MINISIGN_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==
+";
+
+    #[test]
+    fn test_url_defaults_next_to_artifact() {
+        // Arrange
+        let mut signature = Signature {
+            format: SignatureFormat::Minisign,
+            url: None,
+            public_key: Some(MINISIGN_PUBLIC_KEY.to_string()),
+            keyring: None,
+            identity: None,
+            issuer: None,
+            trust_root: None,
+        };
+
+        // Act
+        let default = url(
+            &signature,
+            "1.0.0",
+            "linux",
+            "amd64",
+            "https://example.com/tool.zip",
+        );
+        signature.url = Some("https://example.com/{{version}}/{{os}}-{{arch}}.sig".to_string());
+        let templated = url(
+            &signature,
+            "1.0.0",
+            "linux",
+            "amd64",
+            "https://example.com/tool.zip",
+        );
+
+        // Assert
+        assert_eq!(default, "https://example.com/tool.zip.minisig");
+        assert_eq!(templated, "https://example.com/1.0.0/linux-amd64.sig");
+    }
+
+    #[test]
+    fn test_verify_minisign_signature() {
+        // Arrange
+        let public_key_file = format!(
+            "untrusted comment: minisign public key\n{}\n",
+            MINISIGN_PUBLIC_KEY
+        );
+        let verify_minisign = |content: &[u8], signature: &str| {
+            verify(
+                SignatureFormat::Minisign,
+                public_key_file.as_bytes(),
+                content,
+                signature.as_bytes(),
+            )
+        };
+
+        // Act
+        let valid = verify_minisign(b"test", MINISIGN_SIGNATURE);
+        let tampered = verify_minisign(b"tset", MINISIGN_SIGNATURE);
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid);
+        assert!(tampered.is_err());
+        assert!(verify_minisign(b"test", "not a signature").is_err());
+    }
+
+    #[test]
+    fn test_verify_minisign_legacy_signature() {
+        // Arrange
+        let engine = base64::engine::general_purpose::STANDARD;
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        let public_key = [&b"Ed"[..], &key_id, signing_key.verifying_key().as_bytes()].concat();
+        let signed = signing_key.sign(b"test").to_bytes();
+        let trusted_comment = "timestamp:1633700835\tfile:test";
+        let global = signing_key
+            .sign(&[&signed[..], trusted_comment.as_bytes()].concat())
+            .to_bytes();
+        let signature = format!(
+            "untrusted comment: legacy signature\n{}\ntrusted comment: {}\n{}\n",
+            engine.encode([&b"Ed"[..], &key_id, &signed].concat()),
+            trusted_comment,
+            engine.encode(global)
+        );
+        let verify_legacy = |content: &[u8]| {
+            verify(
+                SignatureFormat::Minisign,
+                engine.encode(&public_key).as_bytes(),
+                content,
+                signature.as_bytes(),
+            )
+        };
+
+        // Act
+        let valid = verify_legacy(b"test");
+        let tampered = verify_legacy(b"tset");
+
+        // Assert
+        assert!(valid.is_ok(), "{:?}", valid);
+        assert!(tampered.is_err());
+    }
+
+    #[test]
+    fn test_verify_minisign_rejects_other_key() {
+        // Arrange
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut public_key = engine.decode(MINISIGN_PUBLIC_KEY).unwrap();
+        let other_key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]).verifying_key();
+        public_key[10..].copy_from_slice(other_key.as_bytes());
+
+        // Act
+        let result = verify(
+            SignatureFormat::Minisign,
+            engine.encode(public_key).as_bytes(),
+            &b"test"[..],
+            MINISIGN_SIGNATURE.as_bytes(),
+        );
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_ed25519_signature_in_any_encoding() {
+        // Arrange
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let public_key = signing_key.verifying_key().to_bytes();
+        let signed = signing_key.sign(b"content").to_bytes();
+        let engine = base64::engine::general_purpose::STANDARD;
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            engine.encode([&ED25519_SPKI_PREFIX[..], &public_key].concat())
+        );
+        let hex: String = public_key
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        // Act
+        let raw = verify(
+            SignatureFormat::Ed25519,
+            pem.as_bytes(),
+            &b"content"[..],
+            &signed,
+        );
+        let encoded = verify(
+            SignatureFormat::Ed25519,
+            hex.as_bytes(),
+            &b"content"[..],
+            engine.encode(signed).as_bytes(),
+        );
+        let tampered = verify(
+            SignatureFormat::Ed25519,
+            engine.encode(public_key).as_bytes(),
+            &b"tampered"[..],
+            &signed,
+        );
+
+        // Assert
+        assert!(raw.is_ok(), "{:?}", raw);
+        assert!(encoded.is_ok(), "{:?}", encoded);
+        assert_eq!(tampered.err().as_deref(), Some("Signature does not match"));
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+static ARCH_MAPPING: std::sync::LazyLock<HashMap<&str, &str>> = std::sync::LazyLock::new(|| {
+    let mut map = HashMap::new();
+    map.insert("x86_64", "amd64");
+    map.insert("aarch64", "arm64");
+    map
+});
+
+/// Maps a Rust architecture name (`std::env::consts::ARCH`) to the name used
+/// in `platforms` and in release artifact names.
+pub fn map_arch(arch: &str) -> Result<&'static str, Box<dyn std::error::Error>> {
+    ARCH_MAPPING
+        .get(arch)
+        .copied()
+        .ok_or_else(|| "Architecture not found".into())
+}
+
+/// Expands the `{{version}}`, `{{os}}` and `{{arch}}` placeholders in `pattern`.
+pub fn render(pattern: &str, version: &str, os: &str, arch: &str) -> String {
+    pattern
+        .replace("{{version}}", version)
+        .replace("{{os}}", os)
+        .replace("{{arch}}", arch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_replaces_all_placeholders() {
+        // Act
+        let rendered = render(
+            "node-v{{version}}-{{os}}-{{arch}}/{{version}}",
+            "20.0.0",
+            "linux",
+            "amd64",
+        );
+
+        // Assert
+        assert_eq!(rendered, "node-v20.0.0-linux-amd64/20.0.0");
+    }
+
+    #[test]
+    fn test_map_arch() {
+        assert_eq!(map_arch("x86_64").unwrap(), "amd64");
+        assert_eq!(map_arch("aarch64").unwrap(), "arm64");
+        assert!(map_arch("sparc").is_err());
+    }
+}
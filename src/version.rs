@@ -0,0 +1,571 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use semver::{Version, VersionReq};
+
+use crate::credentials::CredentialSources;
+use crate::github;
+use crate::models::configuration::{File, Source, VersionSource, VersionSourceType};
+use crate::models::github::Release;
+use crate::models::resolved_version::ResolvedVersion;
+use crate::providers::authenticated_downloader::authorize;
+use crate::providers::http_download;
+
+#[derive(Debug, PartialEq)]
+pub enum Requirement {
+    /// Used as it is, without listing versions.
+    Exact,
+    /// The highest version that is not a pre-release.
+    Latest,
+    Range(VersionReq),
+}
+
+/// Tells an exact version from `latest` or a requirement. Only versions with
+/// an operator, a wildcard or a comma are requirements, so that a plain
+/// `1.22` stays the exact version it always was.
+pub fn parse_requirement(version: &str) -> Result<Requirement, Box<dyn std::error::Error>> {
+    let version = version.trim();
+    if version == "latest" {
+        return Ok(Requirement::Latest);
+    }
+    if version.starts_with(['^', '~', '>', '<', '=']) || version.contains(['*', ',']) {
+        return VersionReq::parse(version)
+            .map(Requirement::Range)
+            .map_err(|e| format!("Invalid version requirement {}, {}", version, e).into());
+    }
+    Ok(Requirement::Exact)
+}
+
+/// Parses a listed version leniently, accepting a missing minor or patch
+/// number as in `1.22`.
+pub fn parse_version(candidate: &str) -> Option<Version> {
+    if let Ok(version) = Version::parse(candidate) {
+        return Some(version);
+    }
+    let (core, rest) = candidate.split_at(candidate.find(['-', '+']).unwrap_or(candidate.len()));
+    let parts = core.split('.').count();
+    if parts >= 3 {
+        return None;
+    }
+    Version::parse(&format!("{}{}{}", core, ".0".repeat(3 - parts), rest)).ok()
+}
+
+/// The highest of `candidates` that satisfies `requirement`, as listed but
+/// without `prefix` and a leading `v`.
+pub fn select(
+    candidates: &[String],
+    requirement: &Requirement,
+    prefix: Option<&str>,
+) -> Option<String> {
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.trim();
+            let candidate = prefix
+                .and_then(|prefix| candidate.strip_prefix(prefix))
+                .unwrap_or(candidate);
+            let candidate = candidate.strip_prefix('v').unwrap_or(candidate);
+            let version = parse_version(candidate)?;
+            let matches = match requirement {
+                Requirement::Exact => false,
+                Requirement::Latest => version.pre.is_empty(),
+                Requirement::Range(requirement) => requirement.matches(&version),
+            };
+            matches.then(|| (version, candidate.to_string()))
+        })
+        .max_by(|(first, _), (second, _)| first.cmp(second))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Versions in a JSON index: a list of versions, of objects with a
+/// `version`, `tag_name` or `name`, or an object with such a list under
+/// `versions` or `releases`, or keyed by version there.
+pub fn versions_from_index(content: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let index: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid version index, {}", e))?;
+    let list = match &index {
+        serde_json::Value::Object(object) => object
+            .get("versions")
+            .or_else(|| object.get("releases"))
+            .ok_or("No versions or releases in the version index")?,
+        _ => &index,
+    };
+    match list {
+        serde_json::Value::Array(items) => Ok(items
+            .iter()
+            .filter_map(|item| match item {
+                serde_json::Value::String(version) => Some(version.clone()),
+                serde_json::Value::Object(object) => ["version", "tag_name", "name"]
+                    .iter()
+                    .find_map(|key| object.get(*key)?.as_str())
+                    .map(str::to_string),
+                _ => None,
+            })
+            .collect()),
+        serde_json::Value::Object(object) => Ok(object.keys().cloned().collect()),
+        _ => Err("Unexpected version index".into()),
+    }
+}
+
+/// Versions in a directory listing: the last path segment of every link, or
+/// every word when the listing is plain text.
+pub fn versions_from_listing(content: &str) -> Vec<String> {
+    let links: Vec<String> = content
+        .split("href=")
+        .skip(1)
+        .filter_map(|rest| {
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let link = rest[1..].split(quote).next()?;
+            let link = link.split(['?', '#']).next()?.trim_end_matches('/');
+            link.rsplit('/').next().map(str::to_string)
+        })
+        .filter(|link| !link.is_empty())
+        .collect();
+    if !links.is_empty() {
+        return links;
+    }
+    content.split_whitespace().map(str::to_string).collect()
+}
+
+fn source_type(configuration: &File) -> Result<VersionSourceType, Box<dyn std::error::Error>> {
+    if let Some(source_type) = configuration
+        .versions
+        .as_ref()
+        .and_then(|versions| versions.source_type)
+    {
+        return Ok(source_type);
+    }
+    if configuration.source == Source::Github {
+        return Ok(VersionSourceType::Github);
+    }
+    Err(format!(
+        "Version {} needs versions.type to be resolved",
+        configuration.version
+    )
+    .into())
+}
+
+/// Describes where versions are listed, so a cached resolution from
+/// elsewhere is not reused.
+fn source_key(
+    configuration: &File,
+    source_type: VersionSourceType,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match source_type {
+        VersionSourceType::Github => Ok(format!(
+            "github {}/repos/{}",
+            github::api_url(configuration),
+            github::repo(configuration)?
+        )),
+        VersionSourceType::Index | VersionSourceType::Listing => {
+            let url = configuration
+                .versions
+                .as_ref()
+                .and_then(|versions| versions.url.as_deref())
+                .ok_or("The index and listing version sources require versions.url")?;
+            let name = match source_type {
+                VersionSourceType::Index => "index",
+                _ => "listing",
+            };
+            Ok(format!("{} {}", name, url))
+        }
+    }
+}
+
+async fn list_versions(
+    configuration: &File,
+    source_type: VersionSourceType,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let client = http_download::client();
+    match source_type {
+        VersionSourceType::Github => {
+            let url = format!(
+                "{}/repos/{}/releases?per_page=100",
+                github::api_url(configuration),
+                github::repo(configuration)?
+            );
+            let credential = github::credential(configuration).await?;
+            let content = http_download::fetch_text(&client, &url, &|request| {
+                let request =
+                    request.header(reqwest::header::ACCEPT, "application/vnd.github+json");
+                match &credential {
+                    Some(credential) => authorize(request, credential),
+                    None => request,
+                }
+            })
+            .await?;
+            let releases: Vec<Release> = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse releases from {}, {}", url, e))?;
+            Ok(releases
+                .into_iter()
+                .filter(|release| !release.draft)
+                .map(|release| release.tag_name)
+                .collect())
+        }
+        VersionSourceType::Index | VersionSourceType::Listing => {
+            let url = configuration
+                .versions
+                .as_ref()
+                .and_then(|versions| versions.url.as_deref())
+                .ok_or("The index and listing version sources require versions.url")?;
+            let credential = CredentialSources::from_environment()
+                .resolve(None, url)
+                .await?;
+            let content = http_download::fetch_text(&client, url, &|request| match &credential {
+                Some(credential) => authorize(request, credential),
+                None => request,
+            })
+            .await?;
+            if source_type == VersionSourceType::Index {
+                versions_from_index(&content)
+            } else {
+                Ok(versions_from_listing(&content))
+            }
+        }
+    }
+}
+
+/// Resolves the version of `configuration`, reusing the resolution cached
+/// in `cache_path` while it is younger than the configured TTL, and past it
+/// when listing versions fails.
+pub async fn resolve(
+    configuration: &File,
+    cache_path: &Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let requirement = parse_requirement(&configuration.version)?;
+    if requirement == Requirement::Exact {
+        return Ok(configuration.version.clone());
+    }
+    if configuration.checksums_url.is_none() && configuration.source != Source::Github {
+        return Err(format!(
+            "Version {} of {} is not exact; non-exact versions need checksumsUrl, \
+            as the digests under platforms only match one version",
+            configuration.version, configuration.name
+        )
+        .into());
+    }
+    let source_type = source_type(configuration)?;
+    let source = source_key(configuration, source_type)?;
+    let ttl = configuration
+        .versions
+        .as_ref()
+        .map_or_else(VersionSource::default_ttl_seconds, |versions| {
+            versions.ttl_seconds
+        });
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let cached = tokio::fs::read_to_string(cache_path)
+        .await
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<ResolvedVersion>(&content).ok())
+        .filter(|cached| cached.requirement == configuration.version && cached.source == source);
+    if let Some(cached) = &cached
+        && now.saturating_sub(cached.resolved_at) < ttl
+    {
+        return Ok(cached.version.clone());
+    }
+
+    let candidates = match list_versions(configuration, source_type).await {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            let Some(cached) = cached else {
+                return Err(e);
+            };
+            eprintln!(
+                "Failed to list versions, {}; using {} resolved earlier",
+                e, cached.version
+            );
+            return Ok(cached.version);
+        }
+    };
+    let prefix = configuration
+        .versions
+        .as_ref()
+        .and_then(|versions| versions.prefix.as_deref());
+    let version = select(&candidates, &requirement, prefix).ok_or_else(|| {
+        format!(
+            "No version of {} matches {}",
+            configuration.name, configuration.version
+        )
+    })?;
+
+    let resolved = ResolvedVersion {
+        requirement: configuration.version.clone(),
+        source,
+        version: version.clone(),
+        resolved_at: now,
+    };
+    tokio::fs::write(cache_path, serde_yaml::to_string(&resolved)?)
+        .await
+        .map_err(|e| format!("Failed to write file {}, {}", cache_path.display(), e))?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::http_download::test_support::get_available_port;
+    use iron::{Iron, IronResult, Listening, Request, Response, status};
+
+    /// Serves a listing of the tool's releases at `/tool/`.
+    fn listing_server() -> (Listening, u16) {
+        let port = get_available_port().expect("No available port found");
+        let handler = |req: &mut Request| -> IronResult<Response> {
+            match req.url.path().join("/").as_str() {
+                "tool/" => Ok(Response::with((
+                    status::Ok,
+                    "<a href=\"v1.4.0/\">v1.4.0/</a>\n\
+                    <a href=\"v1.5.2/\">v1.5.2/</a>\n\
+                    <a href=\"v2.0.0/\">v2.0.0/</a>\n",
+                ))),
+                _ => Ok(Response::with(status::NotFound)),
+            }
+        };
+        (Iron::new(handler).http(("127.0.0.1", port)).unwrap(), port)
+    }
+
+    fn candidates(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|version| version.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_requirement() {
+        // Assert
+        assert_eq!(parse_requirement("1.22").unwrap(), Requirement::Exact);
+        assert_eq!(parse_requirement("1.5.0").unwrap(), Requirement::Exact);
+        assert_eq!(parse_requirement("latest").unwrap(), Requirement::Latest);
+        assert!(matches!(
+            parse_requirement("^1.11").unwrap(),
+            Requirement::Range(_)
+        ));
+        assert!(matches!(
+            parse_requirement(">=1.2, <2").unwrap(),
+            Requirement::Range(_)
+        ));
+        assert!(parse_requirement("^one").is_err());
+    }
+
+    #[test]
+    fn test_select_latest_skips_pre_releases() {
+        // Arrange
+        let candidates = candidates(&["v1.9.0", "v1.10.0", "v1.11.0-rc.1", "v1.2.0"]);
+
+        // Act
+        let version = select(&candidates, &Requirement::Latest, None);
+
+        // Assert
+        assert_eq!(version.as_deref(), Some("1.10.0"));
+    }
+
+    #[test]
+    fn test_select_range() {
+        // Arrange
+        let candidates = candidates(&["1.4.9", "1.5.0", "1.5.3", "1.6.0", "2.0.0"]);
+
+        // Act
+        let tilde = select(&candidates, &parse_requirement("~1.5.0").unwrap(), None);
+        let caret = select(&candidates, &parse_requirement("^1.4").unwrap(), None);
+
+        // Assert
+        assert_eq!(tilde.as_deref(), Some("1.5.3"));
+        assert_eq!(caret.as_deref(), Some("1.6.0"));
+    }
+
+    #[test]
+    fn test_select_strips_prefix_and_accepts_short_versions() {
+        // Arrange
+        let candidates = candidates(&["go1.21", "go1.22.1", "go1.22", "go1.23rc1"]);
+
+        // Act
+        let version = select(
+            &candidates,
+            &parse_requirement("~1.22").unwrap(),
+            Some("go"),
+        );
+
+        // Assert
+        assert_eq!(version.as_deref(), Some("1.22.1"));
+    }
+
+    #[test]
+    fn test_versions_from_index() {
+        // Assert
+        assert_eq!(
+            versions_from_index(r#"["1.0.0", "1.1.0"]"#).unwrap(),
+            candidates(&["1.0.0", "1.1.0"])
+        );
+        assert_eq!(
+            versions_from_index(r#"[{"version": "go1.22.0", "stable": true}]"#).unwrap(),
+            candidates(&["go1.22.0"])
+        );
+        assert_eq!(
+            versions_from_index(r#"{"name": "terraform", "versions": {"1.5.0": {}, "1.6.0": {}}}"#)
+                .unwrap(),
+            candidates(&["1.5.0", "1.6.0"])
+        );
+        assert!(versions_from_index(r#"{"name": "terraform"}"#).is_err());
+    }
+
+    #[test]
+    fn test_versions_from_listing() {
+        // Arrange
+        let html = r#"<ul>
+<li><a href="../">../</a></li>
+<li><a href="/terraform/1.6.0/">terraform_1.6.0</a></li>
+<li><a href='/terraform/1.5.7/?x=1'>terraform_1.5.7</a></li>
+</ul>"#;
+
+        // Assert
+        assert_eq!(
+            versions_from_listing(html),
+            candidates(&["..", "1.6.0", "1.5.7"])
+        );
+        assert_eq!(
+            versions_from_listing("1.0.0\n1.1.0\n"),
+            candidates(&["1.0.0", "1.1.0"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_uses_fresh_cache() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("version");
+        let configuration: File = serde_yaml::from_str(
+            r#"
+name: tool
+version: ^1.5
+urlPattern: http://127.0.0.1:1/tool
+checksumsUrl: http://127.0.0.1:1/tool.sha256
+versions:
+    type: listing
+    url: http://127.0.0.1:1/tool/
+"#,
+        )
+        .unwrap();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let cached = ResolvedVersion {
+            requirement: "^1.5".to_string(),
+            source: "listing http://127.0.0.1:1/tool/".to_string(),
+            version: "1.5.7".to_string(),
+            resolved_at: now - 60,
+        };
+        tokio::fs::write(&cache_path, serde_yaml::to_string(&cached).unwrap())
+            .await
+            .unwrap();
+
+        // Act
+        let version = resolve(&configuration, &cache_path).await;
+
+        // Assert
+        assert_eq!(version.unwrap(), "1.5.7");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_falls_back_to_stale_cache() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("version");
+        let configuration: File = serde_yaml::from_str(
+            r#"
+name: tool
+version: latest
+urlPattern: http://127.0.0.1:1/tool
+checksumsUrl: http://127.0.0.1:1/tool.sha256
+versions:
+    type: index
+    url: http://127.0.0.1:1/index.json
+    ttlSeconds: 10
+"#,
+        )
+        .unwrap();
+        let cached = ResolvedVersion {
+            requirement: "latest".to_string(),
+            source: "index http://127.0.0.1:1/index.json".to_string(),
+            version: "2.0.0".to_string(),
+            resolved_at: 0,
+        };
+        tokio::fs::write(&cache_path, serde_yaml::to_string(&cached).unwrap())
+            .await
+            .unwrap();
+
+        // Act
+        let version = resolve(&configuration, &cache_path).await;
+
+        // Assert
+        assert_eq!(version.unwrap(), "2.0.0");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_lists_versions_from_server() {
+        // Arrange
+        let (mut server, port) = listing_server();
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("version");
+        let configuration: File = serde_yaml::from_str(&format!(
+            r#"
+name: tool
+version: ^1.4
+urlPattern: http://127.0.0.1:{0}/tool/{{{{version}}}}/tool
+checksumsUrl: http://127.0.0.1:{0}/tool/{{{{version}}}}/tool.sha256
+versions:
+    type: listing
+    url: http://127.0.0.1:{0}/tool/
+    prefix: v
+"#,
+            port
+        ))
+        .unwrap();
+
+        // Act
+        let version = resolve(&configuration, &cache_path).await;
+
+        // Assert
+        assert_eq!(version.unwrap(), "1.5.2");
+        let cached: ResolvedVersion =
+            serde_yaml::from_str(&tokio::fs::read_to_string(&cache_path).await.unwrap()).unwrap();
+        assert_eq!(cached.version, "1.5.2");
+        assert_eq!(
+            cached.source,
+            format!("listing http://127.0.0.1:{}/tool/", port)
+        );
+
+        // Clean up
+        server.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_requires_checksums_url_for_non_exact_version() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("version");
+        let configuration: File = serde_yaml::from_str(
+            r#"
+name: tool
+version: latest
+urlPattern: http://127.0.0.1:1/tool
+versions:
+    type: listing
+    url: http://127.0.0.1:1/tool/
+"#,
+        )
+        .unwrap();
+
+        // Act
+        let version = resolve(&configuration, &cache_path).await;
+
+        // Assert
+        assert!(
+            version
+                .unwrap_err()
+                .to_string()
+                .contains("non-exact versions need checksumsUrl")
+        );
+    }
+}